@@ -0,0 +1,88 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use bevy::{ecs::system::RunSystemOnce, prelude::*};
+use bevy_snake::{
+    components::{GameEndEvent, Player, Position},
+    game::Rules,
+    grid::GridConfig,
+    occupancy::rebuild_system,
+    snake::{Head, LastTailPosition, Segment, Segments},
+};
+
+/// Time taken by `iters` ticks of `systems` on a [`board`]. A fresh board is
+/// laid out, untimed, before the head reaches the top edge, so the snake only
+/// ever moves into empty space and never laps through its own body.
+pub fn time_ticks<M>(
+    side: u16,
+    length: usize,
+    systems: impl IntoSystemConfigs<M> + Copy,
+    iters: u64,
+) -> Duration {
+    let rows = length.div_ceil(usize::from(side));
+    let room = u64::try_from(usize::from(side) - rows).unwrap();
+    assert!(
+        room > 0,
+        "no room above a snake of {length} on a {side} board"
+    );
+    let mut total = Duration::ZERO;
+    let mut left = iters;
+    while left > 0 {
+        let mut world = board(side, length);
+        let mut schedule = Schedule::default();
+        schedule.add_systems(systems);
+        schedule.initialize(&mut world).unwrap();
+        let ticks = left.min(room);
+        let start = Instant::now();
+        for _ in 0..ticks {
+            schedule.run(&mut world);
+        }
+        total += start.elapsed();
+        left -= ticks;
+    }
+    total
+}
+
+/// A square board with one snake coiled back and forth along the bottom rows,
+/// its head at the top of the coil facing up into empty space.
+pub fn board(side: u16, length: usize) -> World {
+    let grid = GridConfig::new(side, side);
+    let mut world = World::new();
+    world.insert_resource(grid);
+    world.init_resource::<Rules>();
+    world.insert_resource(LastTailPosition::default());
+    world.init_resource::<Events<GameEndEvent>>();
+
+    let width = usize::from(side);
+    let snake: VecDeque<Entity> = (0..length)
+        .map(|index| {
+            let cell = length - 1 - index;
+            let (row, column) = (cell / width, cell % width);
+            let column = if row % 2 == 0 {
+                column
+            } else {
+                width - 1 - column
+            };
+            let position = Position {
+                x: i16::try_from(column).unwrap(),
+                y: i16::try_from(row).unwrap(),
+            };
+            let mut segment = world.spawn((
+                Segment::behind(&Segment { stamp: 0 }, index),
+                Player { id: 0 },
+                position,
+            ));
+            if index == 0 {
+                segment.insert(Head::default());
+            }
+            segment.id()
+        })
+        .collect();
+    let mut segments = Segments::default();
+    segments.push(snake);
+    world.insert_resource(segments);
+    world.run_system_once(rebuild_system);
+    world
+}
@@ -0,0 +1,62 @@
+use bevy::prelude::*;
+use bevy_snake::{
+    components::{Player, Position},
+    occupancy::{Occupancy, Occupant},
+    snake::{movement_system, Head, Segment, Segments},
+};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+mod common;
+
+use common::time_ticks;
+
+const SIDE: u16 = 100;
+const LENGTHS: [usize; 4] = [10, 100, 1000, 5000];
+
+/// The way snakes used to move, for comparison: every segment takes the
+/// place of the one ahead of it, so each tick touches the whole body.
+#[allow(clippy::needless_pass_by_value)]
+fn shifting_system(
+    segments: Res<Segments>,
+    mut occupancy: ResMut<Occupancy>,
+    heads: Query<(Entity, &Player), With<Head>>,
+    mut positions: Query<&mut Position, With<Segment>>,
+) {
+    for (entity, Player { id }) in &heads {
+        let snake = &segments[usize::from(*id)];
+        let (Ok(from), Some(Ok(tail))) = (
+            positions.get(entity).cloned(),
+            snake.back().map(|tail| positions.get(*tail).cloned()),
+        ) else {
+            continue;
+        };
+        for index in (1..snake.len()).rev() {
+            if let Ok(ahead) = positions.get(snake[index - 1]).cloned() {
+                if let Ok(mut position) = positions.get_mut(snake[index]) {
+                    *position = ahead;
+                }
+            }
+        }
+        if let Ok(mut head) = positions.get_mut(entity) {
+            head.y += 1;
+            occupancy.move_occupant(&from, &head, Occupant::Head(*id));
+        }
+        occupancy.move_occupant(&tail, &from, Occupant::Body(*id));
+    }
+}
+
+fn movement(c: &mut Criterion) {
+    let mut group = c.benchmark_group("snake_move");
+    for length in LENGTHS {
+        group.bench_with_input(BenchmarkId::new("ring", length), &length, |b, _| {
+            b.iter_custom(|iters| time_ticks(SIDE, length, movement_system, iters));
+        });
+        group.bench_with_input(BenchmarkId::new("shifting", length), &length, |b, _| {
+            b.iter_custom(|iters| time_ticks(SIDE, length, shifting_system, iters));
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, movement);
+criterion_main!(benches);
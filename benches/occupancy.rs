@@ -0,0 +1,39 @@
+use bevy_snake::{occupancy::Occupancy, rng::GameRng, snake::movement_system};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+mod common;
+
+use common::{board, time_ticks};
+
+/// Boards and snake lengths, from a small game up to the largest we support.
+const CASES: [(u16, usize); 3] = [(20, 100), (50, 500), (100, 1000)];
+
+fn movement(c: &mut Criterion) {
+    let mut group = c.benchmark_group("movement_tick");
+    for (side, length) in CASES {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{side}x{side}/{length}")),
+            &length,
+            |b, _| b.iter_custom(|iters| time_ticks(side, length, movement_system, iters)),
+        );
+    }
+    group.finish();
+}
+
+fn free_cell(c: &mut Criterion) {
+    let mut group = c.benchmark_group("free_cell");
+    for (side, length) in CASES {
+        let world = board(side, length);
+        let occupancy = world.resource::<Occupancy>();
+        let mut rng = GameRng::seeded(0);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{side}x{side}/{length}")),
+            &length,
+            |b, _| b.iter(|| occupancy.random_free(&mut *rng)),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, movement, free_cell);
+criterion_main!(benches);
@@ -23,7 +23,7 @@ impl Size {
     }
 }
 
-#[derive(PartialEq, Debug, Copy, Clone)]
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
 pub enum Direction {
     Left,
     Up,
@@ -43,21 +43,42 @@ impl Direction {
     }
 }
 
-#[derive(Component, Clone, Debug, Event, PartialEq, Eq)]
-pub enum GameEndEvent {
-    GameOver,
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum DeathCause {
+    Wall,
+    OwnBody,
+    OpponentBody,
+    HeadOn,
 }
 
-impl Default for GameEndEvent {
-    fn default() -> Self {
-        Self::GameOver
+impl Display for DeathCause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeathCause::Wall => write!(f, "hit the wall"),
+            DeathCause::OwnBody => write!(f, "bit itself"),
+            DeathCause::OpponentBody => write!(f, "crashed into an opponent"),
+            DeathCause::HeadOn => write!(f, "crashed head-on"),
+        }
     }
 }
 
+#[derive(Clone, Debug, Event, PartialEq, Eq)]
+pub enum GameEndEvent {
+    Crashed {
+        player_id: u8,
+        cause: DeathCause,
+    },
+    /// Every free cell is taken and no food is left to eat.
+    BoardCleared,
+}
+
 impl Display for GameEndEvent {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            GameEndEvent::GameOver => write!(f, "Game Over!"),
+            GameEndEvent::Crashed { player_id, cause } => {
+                write!(f, "Player {} {cause}", u16::from(*player_id) + 1)
+            }
+            GameEndEvent::BoardCleared => write!(f, "Board cleared"),
         }
     }
 }
@@ -95,4 +116,13 @@ mod test {
         assert_eq!(Direction::Right.opposite(), Direction::Left);
         assert_eq!(Direction::Left.opposite(), Direction::Right);
     }
+    #[test]
+    fn game_end_event_names_player_and_cause() {
+        let event = GameEndEvent::Crashed {
+            player_id: 1,
+            cause: DeathCause::OwnBody,
+        };
+
+        assert_eq!(event.to_string(), "Player 2 bit itself");
+    }
 }
@@ -0,0 +1,473 @@
+use bevy::{
+    ecs::system::SystemParam,
+    input::gamepad::{GamepadConnection, GamepadConnectionEvent},
+    prelude::*,
+};
+
+use crate::components::Direction;
+
+/// How far the left stick has to be pushed before it steers.
+const STICK_DEAD_ZONE: f32 = 0.5;
+
+/// Turns pressed in the same frame are queued in this order.
+const DIRECTION_PRIORITY: [Direction; 4] = [
+    Direction::Left,
+    Direction::Down,
+    Direction::Up,
+    Direction::Right,
+];
+
+/// Keys that can be named in settings, matched by their `KeyCode` debug name.
+const NAMED_KEYS: [KeyCode; 50] = [
+    KeyCode::KeyA,
+    KeyCode::KeyB,
+    KeyCode::KeyC,
+    KeyCode::KeyD,
+    KeyCode::KeyE,
+    KeyCode::KeyF,
+    KeyCode::KeyG,
+    KeyCode::KeyH,
+    KeyCode::KeyI,
+    KeyCode::KeyJ,
+    KeyCode::KeyK,
+    KeyCode::KeyL,
+    KeyCode::KeyM,
+    KeyCode::KeyN,
+    KeyCode::KeyO,
+    KeyCode::KeyP,
+    KeyCode::KeyQ,
+    KeyCode::KeyR,
+    KeyCode::KeyS,
+    KeyCode::KeyT,
+    KeyCode::KeyU,
+    KeyCode::KeyV,
+    KeyCode::KeyW,
+    KeyCode::KeyX,
+    KeyCode::KeyY,
+    KeyCode::KeyZ,
+    KeyCode::Digit0,
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+    KeyCode::Numpad0,
+    KeyCode::Numpad1,
+    KeyCode::Numpad2,
+    KeyCode::Numpad3,
+    KeyCode::Numpad4,
+    KeyCode::Numpad5,
+    KeyCode::Numpad6,
+    KeyCode::Numpad7,
+    KeyCode::Numpad8,
+    KeyCode::Numpad9,
+    KeyCode::ArrowUp,
+    KeyCode::ArrowLeft,
+    KeyCode::ArrowDown,
+    KeyCode::ArrowRight,
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Binding {
+    Key(KeyCode),
+    /// A button on the player's assigned gamepad.
+    PadButton(GamepadButtonType),
+    /// The left stick of the player's assigned gamepad, pushed mostly toward
+    /// the given direction.
+    PadStick(Direction),
+}
+
+/// What a single player presses to steer.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PlayerControls {
+    bindings: Vec<(Binding, Direction)>,
+    gamepad: Option<Gamepad>,
+}
+
+impl PlayerControls {
+    /// Keyboard layout given as up, left, down, right, like WASD, plus the
+    /// d-pad and left stick of whichever gamepad gets assigned.
+    #[must_use]
+    pub fn keys(up: KeyCode, left: KeyCode, down: KeyCode, right: KeyCode) -> Self {
+        let mut controls = Self::pad();
+        controls.bindings.splice(
+            0..0,
+            [
+                (Binding::Key(up), Direction::Up),
+                (Binding::Key(left), Direction::Left),
+                (Binding::Key(down), Direction::Down),
+                (Binding::Key(right), Direction::Right),
+            ],
+        );
+        controls
+    }
+
+    /// Only the d-pad and left stick, for players without a keyboard layout.
+    #[must_use]
+    pub fn pad() -> Self {
+        Self {
+            bindings: vec![
+                (Binding::PadButton(GamepadButtonType::DPadUp), Direction::Up),
+                (
+                    Binding::PadButton(GamepadButtonType::DPadLeft),
+                    Direction::Left,
+                ),
+                (
+                    Binding::PadButton(GamepadButtonType::DPadDown),
+                    Direction::Down,
+                ),
+                (
+                    Binding::PadButton(GamepadButtonType::DPadRight),
+                    Direction::Right,
+                ),
+                (Binding::PadStick(Direction::Up), Direction::Up),
+                (Binding::PadStick(Direction::Left), Direction::Left),
+                (Binding::PadStick(Direction::Down), Direction::Down),
+                (Binding::PadStick(Direction::Right), Direction::Right),
+            ],
+            gamepad: None,
+        }
+    }
+
+    #[must_use]
+    pub const fn gamepad(&self) -> Option<Gamepad> {
+        self.gamepad
+    }
+
+    /// Steers `direction` with `binding`, taking it away from any other direction.
+    pub fn bind(&mut self, binding: Binding, direction: Direction) {
+        self.unbind(binding);
+        self.bindings.push((binding, direction));
+    }
+
+    pub fn unbind(&mut self, binding: Binding) {
+        self.bindings.retain(|(bound, _)| *bound != binding);
+    }
+
+    #[must_use]
+    pub fn bindings(&self) -> &[(Binding, Direction)] {
+        &self.bindings
+    }
+
+    /// Every direction requested by a triggered binding, in priority order.
+    pub fn turns(&self, is_triggered: impl Fn(Binding) -> bool) -> Vec<Direction> {
+        DIRECTION_PRIORITY
+            .into_iter()
+            .filter(|direction| {
+                self.bindings
+                    .iter()
+                    .any(|(binding, bound)| bound == direction && is_triggered(*binding))
+            })
+            .collect()
+    }
+}
+
+/// Controls of every player, indexed by player id.
+#[derive(Resource, Clone, Debug, PartialEq, Eq)]
+pub struct Controls(Vec<PlayerControls>);
+
+impl Default for Controls {
+    fn default() -> Self {
+        Self(vec![
+            PlayerControls::keys(KeyCode::KeyW, KeyCode::KeyA, KeyCode::KeyS, KeyCode::KeyD),
+            PlayerControls::keys(
+                KeyCode::ArrowUp,
+                KeyCode::ArrowLeft,
+                KeyCode::ArrowDown,
+                KeyCode::ArrowRight,
+            ),
+            PlayerControls::keys(KeyCode::KeyI, KeyCode::KeyJ, KeyCode::KeyK, KeyCode::KeyL),
+            PlayerControls::keys(
+                KeyCode::Numpad8,
+                KeyCode::Numpad4,
+                KeyCode::Numpad5,
+                KeyCode::Numpad6,
+            ),
+            PlayerControls::pad(),
+            PlayerControls::pad(),
+            PlayerControls::pad(),
+            PlayerControls::pad(),
+        ])
+    }
+}
+
+impl Controls {
+    #[must_use]
+    pub fn player(&self, player_id: u8) -> Option<&PlayerControls> {
+        self.0.get(usize::from(player_id))
+    }
+
+    /// Mutable access for rebinding, adding empty controls for new players.
+    pub fn player_mut(&mut self, player_id: u8) -> &mut PlayerControls {
+        let index = usize::from(player_id);
+        if index >= self.0.len() {
+            self.0.resize_with(index + 1, PlayerControls::default);
+        }
+        &mut self.0[index]
+    }
+
+    /// Replaces a player's bindings, keeping the gamepad they hold.
+    pub fn set(&mut self, player_id: u8, controls: PlayerControls) {
+        let player = self.player_mut(player_id);
+        let gamepad = player.gamepad;
+        *player = PlayerControls {
+            gamepad,
+            ..controls
+        };
+    }
+
+    /// Hands `gamepad` to `player_id`, taking it away from anyone else.
+    pub fn assign_gamepad(&mut self, player_id: u8, gamepad: Gamepad) {
+        self.release_gamepad(gamepad);
+        self.player_mut(player_id).gamepad = Some(gamepad);
+    }
+
+    pub fn release_gamepad(&mut self, gamepad: Gamepad) {
+        self.0
+            .iter_mut()
+            .filter(|player| player.gamepad == Some(gamepad))
+            .for_each(|player| player.gamepad = None);
+    }
+
+    /// First player still steering with the keyboard only.
+    #[must_use]
+    pub fn player_without_gamepad(&self) -> Option<u8> {
+        self.0
+            .iter()
+            .position(|player| player.gamepad.is_none())
+            .and_then(|index| u8::try_from(index).ok())
+    }
+}
+
+/// Everything a binding can be read from. Gamepad input is optional so the
+/// game still runs without the gamepad plugin.
+#[derive(SystemParam)]
+pub struct SteeringInput<'w> {
+    keys: Res<'w, ButtonInput<KeyCode>>,
+    pad_buttons: Option<Res<'w, ButtonInput<GamepadButton>>>,
+    pad_axes: Option<Res<'w, Axis<GamepadAxis>>>,
+}
+
+impl SteeringInput<'_> {
+    /// Turns asked for during this frame: keys and buttons pressed since the
+    /// last frame, and wherever the stick is held.
+    #[must_use]
+    pub fn turns(&self, controls: &PlayerControls) -> Vec<Direction> {
+        controls.turns(|binding| self.is_triggered(controls.gamepad, binding))
+    }
+
+    fn is_triggered(&self, gamepad: Option<Gamepad>, binding: Binding) -> bool {
+        match (binding, gamepad) {
+            (Binding::Key(key), _) => self.keys.just_pressed(key),
+            (Binding::PadButton(button_type), Some(gamepad)) => {
+                self.pad_buttons.as_ref().is_some_and(|buttons| {
+                    buttons.just_pressed(GamepadButton::new(gamepad, button_type))
+                })
+            }
+            (Binding::PadStick(direction), Some(gamepad)) => {
+                self.pad_axes.as_ref().is_some_and(|axes| {
+                    let axis = |axis_type| {
+                        axes.get(GamepadAxis::new(gamepad, axis_type))
+                            .unwrap_or(0.0)
+                    };
+                    stick_direction(
+                        axis(GamepadAxisType::LeftStickX),
+                        axis(GamepadAxisType::LeftStickY),
+                    ) == Some(direction)
+                })
+            }
+            (_, None) => false,
+        }
+    }
+}
+
+/// Direction of the dominant stick axis, ignoring anything inside the dead zone.
+#[must_use]
+pub fn stick_direction(x: f32, y: f32) -> Option<Direction> {
+    if x.abs().max(y.abs()) < STICK_DEAD_ZONE {
+        None
+    } else if x.abs() > y.abs() {
+        Some(if x > 0.0 {
+            Direction::Right
+        } else {
+            Direction::Left
+        })
+    } else {
+        Some(if y > 0.0 {
+            Direction::Up
+        } else {
+            Direction::Down
+        })
+    }
+}
+
+/// Gives newly connected pads to the first player without one and frees
+/// pads that disconnect, so controllers can come and go mid-session.
+pub fn gamepad_connection_system(
+    mut controls: ResMut<Controls>,
+    mut connection_events: EventReader<GamepadConnectionEvent>,
+) {
+    for event in connection_events.read() {
+        match event.connection {
+            GamepadConnection::Connected(_) => {
+                let already_assigned = controls
+                    .0
+                    .iter()
+                    .any(|player| player.gamepad == Some(event.gamepad));
+                if let (false, Some(player_id)) =
+                    (already_assigned, controls.player_without_gamepad())
+                {
+                    controls.assign_gamepad(player_id, event.gamepad);
+                }
+            }
+            GamepadConnection::Disconnected => controls.release_gamepad(event.gamepad),
+        }
+    }
+}
+
+#[must_use]
+pub fn parse_key_code(name: &str) -> Option<KeyCode> {
+    NAMED_KEYS
+        .into_iter()
+        .find(|key| format!("{key:?}") == name)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bevy::{ecs::system::RunSystemOnce, input::gamepad::GamepadInfo};
+
+    #[test]
+    fn default_controls_use_wasd_and_arrows() {
+        let controls = Controls::default();
+        let pressed = |key| move |binding| binding == Binding::Key(key);
+
+        let player_one = controls.player(0).unwrap();
+        assert_eq!(
+            player_one.turns(pressed(KeyCode::KeyA)),
+            vec![Direction::Left]
+        );
+        assert!(player_one.turns(pressed(KeyCode::ArrowLeft)).is_empty());
+
+        let player_two = controls.player(1).unwrap();
+        assert_eq!(
+            player_two.turns(pressed(KeyCode::ArrowDown)),
+            vec![Direction::Down]
+        );
+        assert!(controls.player(8).is_none());
+    }
+
+    #[test]
+    fn rebinding_moves_key_to_new_direction() {
+        let mut controls = Controls::default();
+
+        controls
+            .player_mut(0)
+            .bind(Binding::Key(KeyCode::KeyW), Direction::Left);
+
+        let player_one = controls.player(0).unwrap();
+        assert_eq!(
+            player_one.turns(|binding| binding == Binding::Key(KeyCode::KeyW)),
+            vec![Direction::Left]
+        );
+        assert_eq!(player_one.bindings().len(), 12);
+    }
+
+    #[test]
+    fn stick_inside_dead_zone_does_not_steer() {
+        assert_eq!(stick_direction(0.2, -0.4), None);
+        assert_eq!(stick_direction(0.9, -0.4), Some(Direction::Right));
+        assert_eq!(stick_direction(-0.3, -0.7), Some(Direction::Down));
+    }
+
+    #[test]
+    fn gamepads_are_assigned_and_released_on_hot_plug() {
+        // Setup
+        let mut app = App::new();
+        app.init_resource::<Controls>()
+            .add_event::<GamepadConnectionEvent>()
+            .add_systems(Update, gamepad_connection_system);
+
+        let connected = |id| {
+            GamepadConnectionEvent::new(
+                Gamepad::new(id),
+                GamepadConnection::Connected(GamepadInfo { name: "pad".into() }),
+            )
+        };
+        app.world.send_event(connected(4));
+        app.world.send_event(connected(7));
+        app.update();
+
+        let controls = app.world.resource::<Controls>();
+        assert_eq!(controls.player(0).unwrap().gamepad(), Some(Gamepad::new(4)));
+        assert_eq!(controls.player(1).unwrap().gamepad(), Some(Gamepad::new(7)));
+
+        // Pad 4 unplugged, then plugged back in
+        app.world.send_event(GamepadConnectionEvent::new(
+            Gamepad::new(4),
+            GamepadConnection::Disconnected,
+        ));
+        app.update();
+        assert_eq!(
+            app.world
+                .resource::<Controls>()
+                .player(0)
+                .unwrap()
+                .gamepad(),
+            None
+        );
+
+        app.world.send_event(connected(4));
+        app.update();
+        let controls = app.world.resource::<Controls>();
+        assert_eq!(controls.player(0).unwrap().gamepad(), Some(Gamepad::new(4)));
+        assert_eq!(controls.player(1).unwrap().gamepad(), Some(Gamepad::new(7)));
+    }
+
+    #[test]
+    fn assigned_gamepad_dpad_steers_player() {
+        // Setup
+        let mut app = App::new();
+        let mut controls = Controls::default();
+        controls.assign_gamepad(1, Gamepad::new(0));
+        let mut buttons = ButtonInput::<GamepadButton>::default();
+        buttons.press(GamepadButton::new(
+            Gamepad::new(0),
+            GamepadButtonType::DPadLeft,
+        ));
+        app.insert_resource(controls)
+            .insert_resource(buttons)
+            .init_resource::<ButtonInput<KeyCode>>();
+
+        let turns = app
+            .world
+            .run_system_once(|controls: Res<Controls>, input: SteeringInput| {
+                [0, 1].map(|id| input.turns(controls.player(id).unwrap()))
+            });
+
+        assert_eq!(turns, [vec![], vec![Direction::Left]]);
+
+        // Once held, the button is no longer a new turn
+        app.world
+            .resource_mut::<ButtonInput<GamepadButton>>()
+            .clear();
+        let turns = app
+            .world
+            .run_system_once(|controls: Res<Controls>, input: SteeringInput| {
+                input.turns(controls.player(1).unwrap())
+            });
+
+        assert!(turns.is_empty());
+    }
+
+    #[test]
+    fn key_codes_are_parsed_by_name() {
+        assert_eq!(parse_key_code("KeyZ"), Some(KeyCode::KeyZ));
+        assert_eq!(parse_key_code("ArrowUp"), Some(KeyCode::ArrowUp));
+        assert_eq!(parse_key_code("Z"), None);
+    }
+}
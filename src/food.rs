@@ -1,47 +1,230 @@
-use bevy::{prelude::*, utils::HashSet};
-use rand::random;
+use std::time::Duration;
+
+use bevy::prelude::*;
+use rand::Rng;
 
 use crate::{
-    components::{Position, Size},
-    grid::{GRID_HEIGHT, GRID_WIDTH},
+    components::{GameEndEvent, Position, Size},
+    occupancy::{Occupancy, Occupant},
+    rng::GameRng,
 };
 
-const FOOD_COLOR: Color = Color::rgb(1.0, 1.0, 1.0);
+/// Food with a lifetime starts fading out this long before it disappears.
+const FADE_DURATION: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FoodKind {
+    #[default]
+    Normal,
+    /// Rare and short-lived, worth several segments at once.
+    Golden,
+    /// Takes segments away instead of adding them.
+    Poison,
+    /// Speeds the game up for a few seconds.
+    Speed,
+    /// Slows the game down for a few seconds.
+    Slow,
+}
+
+impl FoodKind {
+    pub const ALL: [FoodKind; 5] = [
+        FoodKind::Normal,
+        FoodKind::Golden,
+        FoodKind::Poison,
+        FoodKind::Speed,
+        FoodKind::Slow,
+    ];
+
+    #[must_use]
+    pub const fn color(self) -> Color {
+        match self {
+            FoodKind::Normal => Color::rgb(1.0, 1.0, 1.0),
+            FoodKind::Golden => Color::rgb(1.0, 0.84, 0.0),
+            FoodKind::Poison => Color::rgb(0.5, 0.0, 0.35),
+            FoodKind::Speed => Color::rgb(1.0, 0.35, 0.35),
+            FoodKind::Slow => Color::rgb(0.35, 0.55, 1.0),
+        }
+    }
+
+    /// Relative chance of this kind being picked when food spawns.
+    #[must_use]
+    pub const fn weight(self) -> u32 {
+        match self {
+            FoodKind::Normal => 70,
+            FoodKind::Golden => 5,
+            FoodKind::Poison => 10,
+            FoodKind::Speed | FoodKind::Slow => 8,
+        }
+    }
+
+    /// How long the food stays on the board before vanishing, if it ever does.
+    #[must_use]
+    pub const fn lifetime(self) -> Option<Duration> {
+        match self {
+            FoodKind::Normal => None,
+            FoodKind::Golden => Some(Duration::from_secs(5)),
+            FoodKind::Poison => Some(Duration::from_secs(12)),
+            FoodKind::Speed | FoodKind::Slow => Some(Duration::from_secs(8)),
+        }
+    }
+
+    /// Segments gained by the snake eating it, negative when it shrinks.
+    #[must_use]
+    pub const fn growth(self) -> i16 {
+        match self {
+            FoodKind::Normal | FoodKind::Speed | FoodKind::Slow => 1,
+            FoodKind::Golden => 3,
+            FoodKind::Poison => -2,
+        }
+    }
+
+    /// Percentage applied to the move interval while the effect lasts, and for how long.
+    #[must_use]
+    pub const fn pace(self) -> Option<(u32, Duration)> {
+        match self {
+            FoodKind::Speed => Some((60, Duration::from_secs(5))),
+            FoodKind::Slow => Some((160, Duration::from_secs(5))),
+            _ => None,
+        }
+    }
+
+    /// Picks a kind from a roll in `0..`[`FoodKind::total_weight`].
+    #[must_use]
+    pub fn from_roll(mut roll: u32) -> Self {
+        for kind in Self::ALL {
+            if roll < kind.weight() {
+                return kind;
+            }
+            roll -= kind.weight();
+        }
+        FoodKind::Normal
+    }
+
+    #[must_use]
+    pub fn total_weight() -> u32 {
+        Self::ALL.iter().map(|kind| kind.weight()).sum()
+    }
+}
+
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Food {
+    pub kind: FoodKind,
+}
 
-#[derive(Component)]
-pub struct Food;
+/// Counts down the time left before food with a lifetime disappears.
+#[derive(Component, Deref, DerefMut)]
+pub struct Expiry(Timer);
 
-#[allow(clippy::cast_possible_wrap)]
+/// Time since the last food spawn, started over with every round.
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct SpawnTimer(Timer);
+
+/// How much food the board holds and how often it is topped up.
+#[derive(Resource, Clone, Debug, PartialEq, Eq)]
+pub struct FoodRules {
+    /// Food on the board at once, never less than one.
+    pub max_food: usize,
+    /// Time between two spawns. Spawns are checked once per move, so they
+    /// land on the first move after the interval is up, and an interval
+    /// shorter than a move still spawns at most once per move.
+    pub interval: Duration,
+    /// Lifetime of food kinds that otherwise stay until eaten.
+    pub lifetime: Option<Duration>,
+}
+
+impl Default for FoodRules {
+    fn default() -> Self {
+        Self {
+            max_food: 3,
+            interval: Duration::from_secs(1),
+            lifetime: None,
+        }
+    }
+}
+
+impl FoodRules {
+    #[must_use]
+    pub fn lifetime(&self, kind: FoodKind) -> Option<Duration> {
+        kind.lifetime().or(self.lifetime)
+    }
+}
+
+/// Drops food on a cell picked uniformly among the free ones. Once there is
+/// no free cell and nothing left to eat, the board is cleared.
 #[allow(clippy::needless_pass_by_value)]
-pub fn spawn_system(mut commands: Commands, positions: Query<&Position>) {
-    let positions_set: HashSet<&Position> = positions.iter().collect();
-
-    if let Some(position) = (0..(GRID_WIDTH * GRID_HEIGHT))
-        .map(|_| Position {
-            x: if cfg!(test) {
-                3
-            } else {
-                (random::<u16>() % GRID_WIDTH) as i16
-            },
-            y: if cfg!(test) {
-                5
-            } else {
-                (random::<u16>() % GRID_HEIGHT) as i16
-            },
-        })
-        .find(|position| !positions_set.contains(position))
-    {
-        commands
-            .spawn(SpriteBundle {
-                sprite: Sprite {
-                    color: FOOD_COLOR,
-                    ..default()
-                },
-                ..default()
-            })
-            .insert(Food)
-            .insert(position)
-            .insert(Size::square(0.8));
+pub fn spawn_system(
+    mut commands: Commands,
+    rules: Res<FoodRules>,
+    mut rng: ResMut<GameRng>,
+    mut occupancy: ResMut<Occupancy>,
+    mut game_end_writer: EventWriter<GameEndEvent>,
+    food: Query<(), With<Food>>,
+) {
+    if occupancy.free_cells() == 0 && food.is_empty() {
+        game_end_writer.send(GameEndEvent::BoardCleared);
+    } else if food.iter().len() >= rules.max_food.max(1) {
+        return;
+    }
+    if let Some(position) = occupancy.random_free(&mut **rng) {
+        let kind = FoodKind::from_roll(rng.gen_range(0..FoodKind::total_weight()));
+        occupancy.add(&position, Occupant::Food);
+        spawn_food(&mut commands, position, kind, rules.lifetime(kind));
+    }
+}
+
+/// Counts down to the next spawn with [`FoodRules::interval`].
+#[allow(clippy::needless_pass_by_value)]
+pub fn spawn_timer_system(time: Res<Time>, rules: Res<FoodRules>, mut timer: ResMut<SpawnTimer>) {
+    if timer.duration() != rules.interval {
+        **timer = Timer::new(rules.interval, TimerMode::Repeating);
+    }
+    timer.tick(time.delta());
+}
+
+/// Run condition pacing [`spawn_system`] with the [`SpawnTimer`].
+#[allow(clippy::needless_pass_by_value)]
+#[must_use]
+pub fn spawn_due(timer: Res<SpawnTimer>) -> bool {
+    timer.just_finished()
+}
+
+pub fn spawn_food(
+    commands: &mut Commands,
+    position: Position,
+    kind: FoodKind,
+    lifetime: Option<Duration>,
+) -> Entity {
+    let mut food = commands.spawn(SpriteBundle {
+        sprite: Sprite {
+            color: kind.color(),
+            ..default()
+        },
+        ..default()
+    });
+    food.insert(Food { kind })
+        .insert(position)
+        .insert(Size::square(0.8));
+    if let Some(lifetime) = lifetime {
+        food.insert(Expiry(Timer::new(lifetime, TimerMode::Once)));
+    }
+    food.id()
+}
+
+/// Fades food out over its last seconds and removes it once expired.
+#[allow(clippy::needless_pass_by_value)]
+pub fn expiry_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut occupancy: ResMut<Occupancy>,
+    mut food: Query<(Entity, &Position, &mut Expiry, &mut Sprite), With<Food>>,
+) {
+    for (entity, position, mut expiry, mut sprite) in &mut food {
+        if expiry.tick(time.delta()).just_finished() {
+            occupancy.remove(position, Occupant::Food);
+            commands.entity(entity).despawn();
+        }
+        let remaining = expiry.remaining().as_secs_f32() / FADE_DURATION.as_secs_f32();
+        sprite.color.set_a(remaining.min(1.0));
     }
 }
 
@@ -50,16 +233,23 @@ mod test {
     use crate::components::Position;
 
     use super::*;
+    use crate::{grid::GridConfig, level::Wall, occupancy::rebuild_system};
+    use bevy::time::TimeUpdateStrategy;
     use proptest::prelude::*;
 
     proptest! {
         #[test]
-        fn spawns_food_inplace(_execution in 0u16..1000) {
+        fn spawns_food_inplace(seed in any::<u64>()) {
             // Setup app
             let mut app = App::new();
 
             // Add startup system
-            app.add_systems(Startup, spawn_system);
+            app.insert_resource(GridConfig::new(10, 10))
+                .init_resource::<Occupancy>()
+                .insert_resource(GameRng::seeded(seed))
+                .init_resource::<FoodRules>()
+                .add_event::<GameEndEvent>()
+                .add_systems(Startup, spawn_system);
 
             // Run systems
             app.update();
@@ -70,30 +260,223 @@ mod test {
                 let x = position.x;
                 let y = position.y;
 
-                assert!(x >= 0 && x <= (GRID_WIDTH -1) as i16);
-                assert!(y >= 0 && y <= (GRID_HEIGHT -1) as i16);
+                assert!((0..10).contains(&x));
+                assert!((0..10).contains(&y));
             })
         }
     }
 
     #[test]
-    fn food_only_spawns_once() {
+    fn food_never_spawns_inside_walls() {
         // Setup
         let mut app = App::new();
+        app.insert_resource(GridConfig::new(10, 10))
+            .init_resource::<Occupancy>()
+            .insert_resource(GameRng::seeded(7))
+            .init_resource::<FoodRules>()
+            .add_event::<GameEndEvent>()
+            .add_systems(Startup, rebuild_system)
+            .add_systems(Update, spawn_system);
+        for x in 0..10 {
+            for y in 0..10 {
+                app.world.spawn((Wall, Position { x, y }));
+            }
+        }
+
+        app.update();
+
+        let mut query = app.world.query_filtered::<&Position, With<Food>>();
+        assert_eq!(query.iter(&app.world).count(), 0);
+        let events = app.world.resource::<Events<GameEndEvent>>();
+        assert_eq!(
+            events.iter_current_update_events().collect::<Vec<_>>(),
+            vec![&GameEndEvent::BoardCleared]
+        );
+    }
 
-        // Add systems
-        app.add_systems(Update, spawn_system);
+    #[test]
+    fn food_lands_on_the_last_free_cell() {
+        // Setup
+        let mut app = App::new();
+        app.insert_resource(GridConfig::new(10, 10))
+            .init_resource::<Occupancy>()
+            .insert_resource(GameRng::seeded(7))
+            .init_resource::<FoodRules>()
+            .add_event::<GameEndEvent>()
+            .add_systems(Startup, rebuild_system)
+            .add_systems(Update, spawn_system);
+        for position in GridConfig::new(10, 10).positions() {
+            if position != (Position { x: 6, y: 2 }) {
+                app.world.spawn((Wall, position));
+            }
+        }
 
-        // Run systems
         app.update();
 
-        let mut query = app.world.query::<(&Food, &Position)>();
-        assert_eq!(query.iter(&app.world).count(), 1);
+        let mut query = app.world.query_filtered::<&Position, With<Food>>();
+        assert_eq!(
+            query.iter(&app.world).collect::<Vec<_>>(),
+            vec![&Position { x: 6, y: 2 }]
+        );
 
-        // Run systems
+        // The board is full but the food can still be eaten
         app.update();
 
-        let mut query = app.world.query::<(&Food, &Position)>();
-        assert_eq!(query.iter(&app.world).count(), 1)
+        assert!(app.world.resource::<Events<GameEndEvent>>().is_empty());
+    }
+
+    #[test]
+    fn kinds_are_picked_by_weight() {
+        assert_eq!(FoodKind::from_roll(0), FoodKind::Normal);
+        assert_eq!(FoodKind::from_roll(69), FoodKind::Normal);
+        assert_eq!(FoodKind::from_roll(70), FoodKind::Golden);
+        assert_eq!(FoodKind::from_roll(75), FoodKind::Poison);
+        assert_eq!(
+            FoodKind::from_roll(FoodKind::total_weight() - 1),
+            FoodKind::Slow
+        );
+    }
+
+    #[test]
+    fn food_with_lifetime_expires() {
+        // Setup
+        let mut app = App::new();
+        app.add_plugins(bevy::time::TimePlugin)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+                250,
+            )))
+            .init_resource::<GridConfig>()
+            .add_systems(
+                Startup,
+                (
+                    |mut commands: Commands| {
+                        let lifetime = FoodKind::Golden.lifetime();
+                        spawn_food(
+                            &mut commands,
+                            Position { x: 1, y: 1 },
+                            FoodKind::Golden,
+                            lifetime,
+                        );
+                        spawn_food(
+                            &mut commands,
+                            Position { x: 2, y: 2 },
+                            FoodKind::Normal,
+                            None,
+                        );
+                    },
+                    rebuild_system,
+                )
+                    .chain(),
+            )
+            .add_systems(Update, expiry_system);
+
+        // The first update only starts the clock
+        for _ in 0..20 {
+            app.update();
+        }
+        let mut query = app.world.query::<&Food>();
+        assert_eq!(query.iter(&app.world).count(), 2);
+        // A quarter of a second left, fading out over two seconds
+        let mut sprites = app.world.query::<(&Food, &Sprite)>();
+        let alphas: Vec<(FoodKind, f32)> = sprites
+            .iter(&app.world)
+            .map(|(food, sprite)| (food.kind, sprite.color.a()))
+            .collect();
+        assert!(alphas.contains(&(FoodKind::Golden, 0.125)));
+        assert!(alphas.contains(&(FoodKind::Normal, 1.0)));
+
+        app.update();
+        assert_eq!(
+            query.iter(&app.world).collect::<Vec<_>>(),
+            vec![&Food::default()]
+        );
+        let occupancy = app.world.resource::<Occupancy>();
+        assert!(occupancy.is_free(&Position { x: 1, y: 1 }));
+        assert!(!occupancy.is_free(&Position { x: 2, y: 2 }));
+    }
+
+    #[test]
+    fn same_seed_spawns_same_food() {
+        let spawned = |seed| {
+            let mut app = App::new();
+            app.insert_resource(GridConfig::new(10, 10))
+                .init_resource::<Occupancy>()
+                .insert_resource(GameRng::seeded(seed))
+                .insert_resource(FoodRules {
+                    max_food: 5,
+                    ..default()
+                })
+                .add_event::<GameEndEvent>()
+                .add_systems(Update, spawn_system);
+            for _ in 0..5 {
+                app.update();
+            }
+            let mut query = app.world.query::<(&Food, &Position)>();
+            let mut food: Vec<(FoodKind, Position)> = query
+                .iter(&app.world)
+                .map(|(food, position)| (food.kind, position.clone()))
+                .collect();
+            food.sort_by_key(|(_, position)| (position.x, position.y));
+            food
+        };
+
+        assert_eq!(spawned(1).len(), 5);
+        assert_eq!(spawned(1), spawned(1));
+        assert_ne!(spawned(1), spawned(2));
+    }
+
+    #[test]
+    fn food_stops_spawning_at_the_limit() {
+        // Setup
+        let mut app = App::new();
+        app.insert_resource(GridConfig::new(10, 10))
+            .init_resource::<Occupancy>()
+            .insert_resource(GameRng::seeded(3))
+            .insert_resource(FoodRules {
+                max_food: 2,
+                lifetime: Some(Duration::from_secs(30)),
+                ..default()
+            })
+            .add_event::<GameEndEvent>()
+            .add_systems(Update, spawn_system);
+
+        for _ in 0..4 {
+            app.update();
+        }
+
+        let mut query = app.world.query_filtered::<&Food, With<Expiry>>();
+        assert_eq!(query.iter(&app.world).count(), 2);
+    }
+
+    #[test]
+    fn spawning_is_paced_by_the_interval() {
+        // Setup
+        let mut app = App::new();
+        app.add_plugins(bevy::time::TimePlugin)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+                200,
+            )))
+            .insert_resource(GridConfig::new(10, 10))
+            .init_resource::<Occupancy>()
+            .insert_resource(GameRng::seeded(3))
+            .insert_resource(FoodRules {
+                max_food: 10,
+                interval: Duration::from_millis(500),
+                ..default()
+            })
+            .init_resource::<SpawnTimer>()
+            .add_event::<GameEndEvent>()
+            .add_systems(
+                Update,
+                (spawn_timer_system, spawn_system.run_if(spawn_due)).chain(),
+            );
+
+        // 1.2 seconds after the clock started
+        for _ in 0..7 {
+            app.update();
+        }
+
+        let mut query = app.world.query::<&Food>();
+        assert_eq!(query.iter(&app.world).count(), 2);
     }
 }
@@ -1,40 +1,541 @@
-use bevy::prelude::*;
+use std::{
+    collections::VecDeque,
+    fmt::{self, Display},
+};
 
-use crate::components::GameEndEvent;
+use bevy::{app::AppExit, prelude::*, window::WindowFocused};
 
-pub fn over_system(mut commands: Commands, mut reader: EventReader<GameEndEvent>) {
-    if reader.read().next().is_some() {
-        commands.spawn_empty().insert(GameEndEvent::GameOver);
-        println!("{}", GameEndEvent::GameOver);
+use crate::{
+    components::{GameEndEvent, Player, Position},
+    food::{self, Food, FoodKind, FoodRules, SpawnTimer},
+    grid::GridConfig,
+    level::Level,
+    occupancy::{Occupancy, Occupant},
+    rng::GameRng,
+    score::{RoundTime, Scores},
+    snake::{self, Head, LastTailPosition, Segment, Segments},
+    speed::{TickRate, Ticks},
+};
+
+const COUNTDOWN_SECONDS: f32 = 3.0;
+pub const MAX_PLAYERS: u8 = 8;
+/// Snakes a versus round needs at the very least.
+pub const MIN_VERSUS_PLAYERS: u8 = 2;
+const TEXT_COLOR: Color = Color::rgb(0.9, 0.9, 0.9);
+const PAUSE_OVERLAY_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.6);
+
+#[derive(States, Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AppState {
+    /// Title screen, waiting for the players to start a round.
+    #[default]
+    Menu,
+    /// Snakes are on the board but frozen until the countdown finishes.
+    Countdown,
+    Playing,
+    /// Round frozen under the pause overlay.
+    Paused,
+    GameOver,
+}
+
+#[derive(Resource, Deref, DerefMut)]
+pub struct Countdown(Timer);
+
+impl Default for Countdown {
+    fn default() -> Self {
+        Self(Timer::from_seconds(COUNTDOWN_SECONDS, TimerMode::Once))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GameMode {
+    /// One snake, playing for the highest score.
+    Classic,
+    /// Several snakes on the same board, the last one alive wins.
+    #[default]
+    Versus,
+}
+
+impl GameMode {
+    #[must_use]
+    pub const fn points_per_food(self) -> u32 {
+        match self {
+            GameMode::Classic => 10,
+            GameMode::Versus => 1,
+        }
+    }
+}
+
+/// What happens when two heads meet in the same cell or swap places.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HeadOnRule {
+    #[default]
+    BothDie,
+    /// The longer snake survives; snakes of equal length both die.
+    LongerWins,
+}
+
+/// How rounds are played. The mode can be switched from the menu between rounds.
+#[derive(Resource, Clone, Debug, PartialEq, Eq)]
+pub struct Rules {
+    pub mode: GameMode,
+    /// Number of snakes in versus rounds, from 1 to [`MAX_PLAYERS`], though
+    /// versus only starts with [`MIN_VERSUS_PLAYERS`] or more.
+    pub players: u8,
+    /// Heads leaving the board come back in on the opposite edge instead of crashing.
+    pub wrap_walls: bool,
+    pub head_on: HeadOnRule,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self {
+            mode: GameMode::default(),
+            players: 2,
+            wrap_walls: false,
+            head_on: HeadOnRule::default(),
+        }
+    }
+}
+
+impl Rules {
+    /// Why a versus round cannot start with these rules on `level`, if it can't.
+    #[must_use]
+    pub fn versus_blocked(&self, level: &Level) -> Option<String> {
+        if self.players < MIN_VERSUS_PLAYERS {
+            Some(format!(
+                "needs {MIN_VERSUS_PLAYERS} players, start with --players {MIN_VERSUS_PLAYERS}"
+            ))
+        } else if level.seats(self.players) {
+            None
+        } else {
+            // Snakes the level has no spawn for would start wherever, walls or not
+            Some(format!(
+                "needs {} spawns, the level has {}",
+                self.players,
+                level.spawns.len()
+            ))
+        }
+    }
+
+    /// Number of snakes spawned for a round in the current mode.
+    #[must_use]
+    pub const fn snakes(&self) -> u8 {
+        match self.mode {
+            GameMode::Classic => 1,
+            GameMode::Versus => self.players,
+        }
+    }
+}
+
+/// How the last round ended: every crash in the order it happened and the
+/// surviving player, if exactly one was left. A cleared board goes to the
+/// longest surviving snake.
+#[derive(Resource, Default, Debug, Clone, PartialEq, Eq)]
+pub struct RoundOutcome {
+    pub winner: Option<u8>,
+    pub crashes: Vec<GameEndEvent>,
+    /// Single-player rounds have no winner, only a final score.
+    pub solo: bool,
+}
+
+impl Display for RoundOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for crash in &self.crashes {
+            writeln!(f, "{crash}")?;
+        }
+        match self.winner {
+            _ if self.solo && self.cleared() => write!(f, "You win!"),
+            _ if self.solo => write!(f, "Game Over!"),
+            Some(player_id) => write!(f, "Player {} wins!", u16::from(player_id) + 1),
+            None => write!(f, "Draw!"),
+        }
+    }
+}
+
+impl RoundOutcome {
+    #[must_use]
+    pub fn cleared(&self) -> bool {
+        self.crashes.contains(&GameEndEvent::BoardCleared)
+    }
+}
+
+/// Starts a fresh round, clearing whatever the previous one left on the board.
+#[derive(Event, Default)]
+pub struct RestartEvent;
+
+#[derive(Component)]
+pub struct MenuScreen;
+
+#[derive(Component)]
+pub struct CountdownScreen;
+
+#[derive(Component)]
+pub struct GameOverScreen;
+
+#[derive(Component)]
+pub struct PauseScreen;
+
+/// Knocks crashed snakes out of the round and ends it once fewer than two
+/// are left, when the only snake of a single-player round dies, or when the
+/// board is cleared.
+#[allow(clippy::needless_pass_by_value)]
+#[allow(clippy::too_many_arguments)]
+pub fn over_system(
+    mut commands: Commands,
+    mut reader: EventReader<GameEndEvent>,
+    rules: Res<Rules>,
+    mut segments: ResMut<Segments>,
+    mut outcome: ResMut<RoundOutcome>,
+    mut occupancy: ResMut<Occupancy>,
+    mut next_state: ResMut<NextState<AppState>>,
+    players: Query<&Player, With<Head>>,
+    bodies: Query<(Entity, &Player, &Position, Has<Head>), With<Segment>>,
+) {
+    let crashes: Vec<GameEndEvent> = reader.read().cloned().collect();
+    if crashes.is_empty() {
+        return;
+    }
+    let knocked_out: Vec<u8> = crashes
+        .iter()
+        .filter_map(|event| match event {
+            GameEndEvent::Crashed { player_id, .. } => Some(*player_id),
+            GameEndEvent::BoardCleared => None,
+        })
+        .collect();
+    let survivors: Vec<u8> = players
+        .iter()
+        .map(|player| player.id)
+        .filter(|id| !knocked_out.contains(id))
+        .collect();
+    let cleared = crashes.contains(&GameEndEvent::BoardCleared);
+
+    if !cleared && survivors.len() >= usize::from(rules.snakes().min(2)) {
+        for (entity, player, position, head) in &bodies {
+            if knocked_out.contains(&player.id) {
+                let occupant = if head {
+                    Occupant::Head(player.id)
+                } else {
+                    Occupant::Body(player.id)
+                };
+                occupancy.remove(position, occupant);
+                commands.entity(entity).despawn();
+            }
+        }
+        for player_id in knocked_out {
+            if let Some(snake) = segments.get_mut(usize::from(player_id)) {
+                snake.clear();
+            }
+        }
+        outcome.crashes.extend(crashes);
+        return;
+    }
+
+    outcome.crashes.extend(crashes);
+    outcome.solo = rules.snakes() == 1;
+    outcome.winner = if outcome.solo {
+        None
+    } else if cleared {
+        longest(&segments, &survivors)
+    } else {
+        match survivors[..] {
+            [winner] => Some(winner),
+            _ => None,
+        }
+    };
+    println!("{}", *outcome);
+    next_state.set(AppState::GameOver);
+}
+
+/// Player with the strictly longest snake among `players`.
+fn longest(segments: &Segments, players: &[u8]) -> Option<u8> {
+    let length = |id: u8| segments.get(usize::from(id)).map_or(0, VecDeque::len);
+    let best = players.iter().map(|id| length(*id)).max()?;
+    match players
+        .iter()
+        .filter(|id| length(**id) == best)
+        .collect::<Vec<_>>()[..]
+    {
+        [winner] => Some(*winner),
+        _ => None,
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+#[allow(clippy::type_complexity)]
+#[allow(clippy::too_many_arguments)]
+pub fn restart_system(
+    mut commands: Commands,
+    mut reader: EventReader<RestartEvent>,
+    grid: Res<GridConfig>,
+    level: Res<Level>,
+    rules: Res<Rules>,
+    food_rules: Res<FoodRules>,
+    mut rng: ResMut<GameRng>,
+    mut tick_rate: ResMut<TickRate>,
+    mut next_state: ResMut<NextState<AppState>>,
+    round_entities: Query<Entity, Or<(With<Segment>, With<Food>)>>,
+) {
+    if reader.read().last().is_none() {
+        return;
+    }
+    for entity in &round_entities {
+        commands.entity(entity).despawn();
+    }
+    let segments = snake::spawn_snakes(&mut commands, &grid, &level, rules.snakes());
+    commands.insert_resource(segments);
+    for position in &level.food {
+        food::spawn_food(
+            &mut commands,
+            position.clone(),
+            FoodKind::Normal,
+            food_rules.lifetime(FoodKind::Normal),
+        );
+    }
+    commands.insert_resource(LastTailPosition::default());
+    commands.insert_resource(RoundOutcome::default());
+    commands.insert_resource(Scores::default());
+    commands.insert_resource(RoundTime::default());
+    commands.insert_resource(Countdown::default());
+    commands.insert_resource(Ticks::default());
+    commands.insert_resource(SpawnTimer::default());
+    rng.restart();
+    tick_rate.reset();
+    next_state.set(AppState::Countdown);
+}
+
+#[allow(clippy::needless_pass_by_value)]
+pub fn restart_input_system(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut restart_writer: EventWriter<RestartEvent>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyR) {
+        restart_writer.send(RestartEvent);
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+pub fn menu_setup(mut commands: Commands, rules: Res<Rules>, level: Res<Level>) {
+    spawn_screen_text(
+        &mut commands,
+        MenuScreen,
+        &format!(
+            "Snake\n\n1 - Classic (1 player)\n2 - Versus ({})\n\nSpace - play {}",
+            rules
+                .versus_blocked(&level)
+                .unwrap_or_else(|| format!("{} players", rules.players)),
+            match rules.mode {
+                GameMode::Classic => "classic",
+                GameMode::Versus => "versus",
+            }
+        ),
+    );
+}
+
+#[allow(clippy::needless_pass_by_value)]
+pub fn menu_input_system(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    level: Res<Level>,
+    mut rules: ResMut<Rules>,
+    mut restart_writer: EventWriter<RestartEvent>,
+) {
+    let mode = if keyboard_input.any_just_pressed([KeyCode::Digit1, KeyCode::Numpad1]) {
+        GameMode::Classic
+    } else if keyboard_input.any_just_pressed([KeyCode::Digit2, KeyCode::Numpad2]) {
+        GameMode::Versus
+    } else if keyboard_input.any_just_pressed([KeyCode::Space, KeyCode::Enter]) {
+        rules.mode
+    } else {
+        return;
+    };
+    if mode == GameMode::Versus && rules.versus_blocked(&level).is_some() {
+        return;
+    }
+    rules.mode = mode;
+    restart_writer.send(RestartEvent);
+}
+
+pub fn countdown_setup(mut commands: Commands) {
+    spawn_screen_text(
+        &mut commands,
+        CountdownScreen,
+        &countdown_label(COUNTDOWN_SECONDS),
+    );
+}
+
+#[allow(clippy::needless_pass_by_value)]
+pub fn countdown_system(
+    time: Res<Time>,
+    mut countdown: ResMut<Countdown>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut texts: Query<&mut Text, With<CountdownScreen>>,
+) {
+    if countdown.tick(time.delta()).just_finished() {
+        next_state.set(AppState::Playing);
+    }
+    for mut text in &mut texts {
+        text.sections[0].value = countdown_label(countdown.remaining_secs());
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+pub fn game_over_setup(mut commands: Commands, outcome: Res<RoundOutcome>) {
+    spawn_screen_text(
+        &mut commands,
+        GameOverScreen,
+        &format!("{}\n\nPress R to restart", *outcome),
+    );
+}
+
+#[allow(clippy::needless_pass_by_value)]
+pub fn pause_input_system(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    pad_buttons: Option<Res<ButtonInput<GamepadButton>>>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if pause_pressed(&keyboard_input, pad_buttons.as_deref()) {
+        next_state.set(AppState::Paused);
+    }
+}
+
+/// Pauses the round as soon as the window loses focus.
+#[allow(clippy::needless_pass_by_value)]
+pub fn focus_lost_system(
+    mut focus_reader: EventReader<WindowFocused>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if focus_reader.read().any(|event| !event.focused) {
+        next_state.set(AppState::Paused);
+    }
+}
+
+pub fn pause_setup(mut commands: Commands) {
+    let overlay = spawn_screen_text(
+        &mut commands,
+        PauseScreen,
+        "Paused\n\nEsc / P - resume\nR - restart\nQ - quit",
+    );
+    commands
+        .entity(overlay)
+        .insert(BackgroundColor(PAUSE_OVERLAY_COLOR));
+}
+
+#[allow(clippy::needless_pass_by_value)]
+pub fn paused_input_system(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    pad_buttons: Option<Res<ButtonInput<GamepadButton>>>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut restart_writer: EventWriter<RestartEvent>,
+    mut exit_writer: EventWriter<AppExit>,
+) {
+    if pause_pressed(&keyboard_input, pad_buttons.as_deref()) {
+        next_state.set(AppState::Playing);
+    } else if keyboard_input.just_pressed(KeyCode::KeyR) {
+        restart_writer.send(RestartEvent);
+    } else if keyboard_input.just_pressed(KeyCode::KeyQ) {
+        exit_writer.send(AppExit);
+    }
+}
+
+/// Esc, P or Start on any gamepad.
+fn pause_pressed(
+    keyboard_input: &ButtonInput<KeyCode>,
+    pad_buttons: Option<&ButtonInput<GamepadButton>>,
+) -> bool {
+    keyboard_input.any_just_pressed([KeyCode::Escape, KeyCode::KeyP])
+        || pad_buttons.is_some_and(|buttons| {
+            buttons
+                .get_just_pressed()
+                .any(|button| button.button_type == GamepadButtonType::Start)
+        })
+}
+
+#[allow(clippy::needless_pass_by_value)]
+pub fn despawn_screen<T: Component>(mut commands: Commands, screen: Query<Entity, With<T>>) {
+    for entity in &screen {
+        commands.entity(entity).despawn_recursive();
     }
 }
 
+fn countdown_label(remaining_secs: f32) -> String {
+    format!("{}", remaining_secs.ceil().max(1.0))
+}
+
+fn spawn_screen_text(commands: &mut Commands, marker: impl Component, value: &str) -> Entity {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .insert(marker)
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(
+                    value,
+                    TextStyle {
+                        font_size: 60.0,
+                        color: TEXT_COLOR,
+                        ..default()
+                    },
+                )
+                .with_text_justify(JustifyText::Center),
+            );
+        })
+        .id()
+}
+
 #[cfg(test)]
 mod test {
 
     use super::*;
     use crate::{
-        components::Position,
-        snake::{self, Head, LastTailPosition, Segments},
+        components::DeathCause, controls::Controls, food::Expiry, occupancy::rebuild_system,
     };
     use bevy::app::App;
+    use bevy::ecs::query::With;
+    use bevy::ecs::system::RunSystemOnce;
+    use std::time::Duration;
 
-    #[test]
-    fn game_end_event_with_game_over() {
-        // Setup
+    fn app_in_play() -> App {
         let mut app = App::new();
 
-        // Sistemas
-        app.insert_resource(Segments::default())
+        app.insert_resource(GridConfig::new(10, 10))
+            .insert_resource(Segments::default())
             .insert_resource(LastTailPosition::default())
-            .add_event::<GameEndEvent>() // <--
-            .add_systems(Startup, snake::spawn_system)
-            .add_systems(Update, snake::movement_system)
+            .insert_resource(RoundOutcome::default())
+            .init_resource::<Rules>()
+            .init_resource::<Level>()
+            .init_resource::<FoodRules>()
+            .insert_resource(GameRng::seeded(0))
+            .init_resource::<Controls>()
+            .insert_state(AppState::Playing)
+            .add_event::<GameEndEvent>()
+            .add_systems(Startup, (snake::spawn_system, rebuild_system).chain())
             .add_systems(
                 Update,
-                snake::movement_input_system.before(snake::movement_system),
-            )
-            .add_systems(Update, over_system.after(snake::movement_system)); // <--
+                (
+                    snake::movement_input_system.before(snake::movement_system),
+                    snake::movement_system,
+                    over_system.after(snake::movement_system),
+                )
+                    .run_if(in_state(AppState::Playing)),
+            );
+        app
+    }
+
+    fn state(app: &App) -> AppState {
+        *app.world.resource::<State<AppState>>().get()
+    }
+
+    #[test]
+    fn game_end_event_with_game_over() {
+        // Setup
+        let mut app = app_in_play();
 
         // tecla para cima
         let mut input = ButtonInput::<KeyCode>::default();
@@ -46,17 +547,21 @@ mod test {
             app.update(); // x: 5, y: 6
         }
 
-        // Verificar que não há componente de game end
-        let mut query = app.world.query::<&GameEndEvent>();
-        assert_eq!(query.iter(&app.world).count(), 0);
+        // Verificar que o jogo continua
+        assert_eq!(state(&app), AppState::Playing);
 
         for _ in 0..20 {
             app.update();
         }
 
-        // Verificar que há componente de game end
-        let mut query = app.world.query::<&GameEndEvent>();
-        assert_eq!(query.iter(&app.world).count(), 2);
+        // Verificar que o jogo terminou
+        assert_eq!(state(&app), AppState::GameOver);
+
+        // As duas cobras batem na parede ao mesmo tempo
+        let outcome = app.world.resource::<RoundOutcome>();
+        assert_eq!(outcome.winner, None);
+        assert_eq!(outcome.crashes.len(), 2);
+        assert_eq!(outcome.to_string().lines().last(), Some("Draw!"));
 
         let mut query = app.world.query_filtered::<&Position, With<Head>>();
         let position_at_gameover = query.iter(&app.world).next().unwrap();
@@ -76,26 +581,11 @@ mod test {
     #[test]
     fn game_end_event_with_game_over_when_moving_left() {
         // Setup
-        let mut app = App::new();
-
-        // Add systems
-        app.insert_resource(Segments::default())
-            .insert_resource(LastTailPosition::default())
-            .add_event::<GameEndEvent>() // <--
-            .add_systems(Startup, snake::spawn_system)
-            .add_systems(Update, snake::movement_system)
-            .add_systems(
-                Update,
-                snake::movement_input_system.before(snake::movement_system),
-            )
-            .add_systems(Update, over_system.after(snake::movement_system)); // <--
+        let mut app = app_in_play();
 
         // Add new input resource
         let mut input = ButtonInput::<KeyCode>::default();
-        #[cfg(debug_assertions)]
         input.press(KeyCode::KeyA);
-        #[cfg(not(debug_assertions))]
-        input.press(KeyCode::ArrowLeft);
         app.insert_resource(input);
 
         // Run systems again
@@ -103,48 +593,488 @@ mod test {
             app.update();
         }
 
-        let mut query = app.world.query::<&GameEndEvent>();
-        assert_eq!(query.iter(&app.world).count(), 0);
+        assert_eq!(state(&app), AppState::Playing);
 
-        for _ in 0..1 {
+        for _ in 0..2 {
             app.update();
         }
 
-        let mut query = app.world.query::<&GameEndEvent>();
-        assert_eq!(query.iter(&app.world).count(), 1);
+        assert_eq!(state(&app), AppState::GameOver);
+        assert_eq!(
+            app.world.resource::<RoundOutcome>(),
+            &RoundOutcome {
+                winner: Some(1),
+                crashes: vec![GameEndEvent::Crashed {
+                    player_id: 0,
+                    cause: DeathCause::Wall
+                }],
+                solo: false,
+            }
+        );
     }
 
     #[test]
     fn game_end_event_with_game_over_when_moving_right() {
+        // Setup
+        let mut app = app_in_play();
+
+        // Add new input resource
+        let mut input = ButtonInput::<KeyCode>::default();
+        input.press(KeyCode::KeyD);
+        app.insert_resource(input);
+
+        // Run systems again
+        for _ in 0..8 {
+            app.update();
+        }
+
+        assert_eq!(state(&app), AppState::GameOver);
+    }
+
+    #[test]
+    fn crash_into_opponent_body_is_reported() {
+        // Setup
+        let mut app = app_in_play();
+        app.insert_resource(ButtonInput::<KeyCode>::default());
+        app.update();
+
+        // Put the second snake's head right above the first head, its body
+        // follows into that cell as the first head gets there
+        let head = app.world.resource::<Segments>()[1][0];
+        *app.world.get_mut::<Position>(head).unwrap() = Position { x: 3, y: 5 };
+        app.world.run_system_once(rebuild_system);
+        app.update();
+        app.update();
+
+        assert_eq!(state(&app), AppState::GameOver);
+        let outcome = app.world.resource::<RoundOutcome>();
+        assert_eq!(outcome.winner, Some(1));
+        assert_eq!(
+            outcome.crashes,
+            vec![GameEndEvent::Crashed {
+                player_id: 0,
+                cause: DeathCause::OpponentBody
+            }]
+        );
+    }
+
+    #[test]
+    fn crashed_snake_is_knocked_out_while_others_play_on() {
+        // Setup
+        let mut app = app_in_play();
+        app.insert_resource(Rules {
+            players: 3,
+            ..default()
+        })
+        .insert_resource(ButtonInput::<KeyCode>::default());
+        app.update();
+
+        // Block the first snake's way with the last snake's body
+        let head = app.world.resource::<Segments>()[2][0];
+        *app.world.get_mut::<Position>(head).unwrap() = Position { x: 3, y: 5 };
+        app.world.run_system_once(rebuild_system);
+        app.update();
+        app.update();
+
+        assert_eq!(state(&app), AppState::Playing);
+        assert!(app.world.resource::<Segments>()[0].is_empty());
+        let mut bodies = app.world.query_filtered::<&Player, With<Segment>>();
+        assert!(bodies.iter(&app.world).all(|player| player.id != 0));
+        let mut query = app.world.query::<(&Head, &Player)>();
+        let mut players: Vec<u8> = query.iter(&app.world).map(|(_, p)| p.id).collect();
+        players.sort_unstable();
+        assert_eq!(players, vec![1, 2]);
+        assert_eq!(app.world.resource::<RoundOutcome>().crashes.len(), 1);
+    }
+
+    #[test]
+    fn classic_round_ends_only_when_the_single_snake_dies() {
+        // Setup
+        let mut app = app_in_play();
+        app.insert_resource(Rules {
+            mode: GameMode::Classic,
+            ..default()
+        });
+
+        let mut input = ButtonInput::<KeyCode>::default();
+        input.press(KeyCode::KeyA);
+        app.insert_resource(input);
+
+        for _ in 0..5 {
+            app.update();
+        }
+        assert_eq!(state(&app), AppState::Playing);
+
+        for _ in 0..2 {
+            app.update();
+        }
+        assert_eq!(state(&app), AppState::GameOver);
+        let outcome = app.world.resource::<RoundOutcome>();
+        assert!(outcome.solo);
+        assert_eq!(outcome.winner, None);
+        assert_eq!(outcome.to_string(), "Player 1 hit the wall\nGame Over!");
+    }
+
+    #[test]
+    fn menu_selects_classic_mode() {
         // Setup
         let mut app = App::new();
+        app.insert_resource(GridConfig::new(10, 10))
+            .init_resource::<Rules>()
+            .init_resource::<Level>()
+            .insert_resource(GameRng::seeded(0))
+            .init_resource::<TickRate>()
+            .init_state::<AppState>()
+            .init_resource::<FoodRules>()
+            .add_event::<RestartEvent>()
+            .add_systems(Update, menu_input_system)
+            .add_systems(Update, restart_system.after(menu_input_system));
 
-        // Add systems
-        app.insert_resource(Segments::default())
+        let mut input = ButtonInput::<KeyCode>::default();
+        input.press(KeyCode::Digit1);
+        app.insert_resource(input);
+        app.update();
+
+        assert_eq!(app.world.resource::<Rules>().mode, GameMode::Classic);
+        let mut query = app.world.query_filtered::<Entity, With<Head>>();
+        assert_eq!(query.iter(&app.world).count(), 1);
+    }
+
+    #[test]
+    fn menu_refuses_versus_the_level_cannot_seat() {
+        // Setup
+        let mut app = App::new();
+        app.insert_resource(GridConfig::new(10, 10))
+            .insert_resource(Rules {
+                mode: GameMode::Classic,
+                players: 8,
+                ..default()
+            })
+            .insert_resource(Level::parse("..........\n.1.....2..\n..........").unwrap())
+            .insert_resource(GameRng::seeded(0))
+            .init_resource::<TickRate>()
+            .init_state::<AppState>()
+            .init_resource::<FoodRules>()
+            .add_event::<RestartEvent>()
+            .add_systems(Update, menu_input_system)
+            .add_systems(Update, restart_system.after(menu_input_system));
+
+        let mut input = ButtonInput::<KeyCode>::default();
+        input.press(KeyCode::Digit2);
+        app.insert_resource(input);
+        app.update();
+
+        let rules = app.world.resource::<Rules>();
+        assert_eq!((rules.mode, rules.players), (GameMode::Classic, 8));
+        assert!(app.world.resource::<Events<RestartEvent>>().is_empty());
+        let mut query = app.world.query_filtered::<Entity, With<Head>>();
+        assert_eq!(query.iter(&app.world).count(), 0);
+    }
+
+    #[test]
+    fn menu_refuses_versus_for_a_single_player() {
+        // Setup
+        let mut app = App::new();
+        app.insert_resource(GridConfig::new(10, 10))
+            .insert_resource(Rules {
+                mode: GameMode::Classic,
+                players: 1,
+                ..default()
+            })
+            .init_resource::<Level>()
+            .add_event::<RestartEvent>()
+            .add_systems(Update, menu_input_system);
+
+        let mut input = ButtonInput::<KeyCode>::default();
+        input.press(KeyCode::Digit2);
+        app.insert_resource(input);
+        app.update();
+
+        let rules = app.world.resource::<Rules>();
+        assert_eq!((rules.mode, rules.players), (GameMode::Classic, 1));
+        assert!(app.world.resource::<Events<RestartEvent>>().is_empty());
+    }
+
+    #[test]
+    fn menu_starts_countdown_on_space() {
+        // Setup
+        let mut app = App::new();
+        app.insert_resource(GridConfig::new(10, 10))
+            .insert_resource(Segments::default())
             .insert_resource(LastTailPosition::default())
-            .add_event::<GameEndEvent>() // <--
-            .add_systems(Startup, snake::spawn_system)
-            .add_systems(Update, snake::movement_system)
+            .init_resource::<Rules>()
+            .init_resource::<Level>()
+            .init_resource::<FoodRules>()
+            .insert_resource(GameRng::seeded(0))
+            .init_resource::<TickRate>()
+            .init_state::<AppState>()
+            .add_event::<RestartEvent>()
+            .add_systems(Update, menu_input_system.run_if(in_state(AppState::Menu)))
+            .add_systems(Update, restart_system.after(menu_input_system));
+
+        let mut input = ButtonInput::<KeyCode>::default();
+        input.press(KeyCode::Space);
+        app.insert_resource(input);
+
+        assert_eq!(state(&app), AppState::Menu);
+        app.update();
+        app.update();
+
+        assert_eq!(state(&app), AppState::Countdown);
+        let mut query = app.world.query_filtered::<Entity, With<Head>>();
+        assert_eq!(query.iter(&app.world).count(), 2);
+    }
+
+    #[test]
+    fn cleared_board_goes_to_the_longest_snake() {
+        // Setup
+        let mut app = app_in_play();
+        app.insert_resource(ButtonInput::<KeyCode>::default());
+        app.update();
+
+        let tail = app
+            .world
+            .spawn((
+                Segment { stamp: -2 },
+                Player { id: 1 },
+                Position { x: 9, y: 0 },
+            ))
+            .id();
+        app.world.resource_mut::<Segments>()[1].push_back(tail);
+        app.world.send_event(GameEndEvent::BoardCleared);
+        app.update();
+        app.update();
+
+        assert_eq!(state(&app), AppState::GameOver);
+        let outcome = app.world.resource::<RoundOutcome>();
+        assert_eq!(outcome.winner, Some(1));
+        assert_eq!(outcome.to_string(), "Board cleared\nPlayer 2 wins!");
+    }
+
+    #[test]
+    fn cleared_board_is_a_solo_victory() {
+        let outcome = RoundOutcome {
+            winner: None,
+            crashes: vec![GameEndEvent::BoardCleared],
+            solo: true,
+        };
+
+        assert_eq!(outcome.to_string(), "Board cleared\nYou win!");
+    }
+
+    #[test]
+    fn restart_clears_board_and_respawns_snakes() {
+        // Setup
+        let mut app = app_in_play();
+        app.add_event::<RestartEvent>()
+            .init_resource::<TickRate>()
+            .add_systems(Update, restart_system);
+        app.world.spawn((Food::default(), Position { x: 1, y: 1 }));
+
+        let mut input = ButtonInput::<KeyCode>::default();
+        input.press(KeyCode::KeyW);
+        app.insert_resource(input);
+
+        // Play until the snakes hit the wall
+        for _ in 0..23 {
+            app.update();
+        }
+        assert_eq!(state(&app), AppState::GameOver);
+
+        // Halfway to the next food spawn
+        let mut timer = SpawnTimer::default();
+        *timer = Timer::from_seconds(1.0, TimerMode::Repeating);
+        timer.tick(Duration::from_millis(500));
+        app.world.insert_resource(timer);
+        app.world.send_event(RestartEvent);
+        app.world.insert_resource(ButtonInput::<KeyCode>::default());
+        app.update();
+        app.update();
+
+        assert_eq!(state(&app), AppState::Countdown);
+        assert_eq!(app.world.resource::<SpawnTimer>().elapsed(), Duration::ZERO);
+        let mut query = app.world.query_filtered::<Entity, With<Food>>();
+        assert_eq!(query.iter(&app.world).count(), 0);
+        let mut query = app.world.query_filtered::<Entity, With<Segment>>();
+        assert_eq!(query.iter(&app.world).count(), 4);
+        let mut query = app
+            .world
+            .query_filtered::<(&Position, &Player), With<Head>>();
+        query.iter(&app.world).for_each(|(position, player)| {
+            if player.id == 0 {
+                assert_eq!(position, &Position { x: 3, y: 3 });
+            }
+        });
+    }
+
+    #[test]
+    fn restart_lays_out_the_level() {
+        // Setup
+        let mut app = app_in_play();
+        app.add_event::<RestartEvent>()
+            .init_resource::<TickRate>()
+            .insert_resource(Level::parse("..........\n.1.....2..\n....*.....").unwrap())
+            .insert_resource(FoodRules {
+                lifetime: Some(Duration::from_secs(5)),
+                ..default()
+            })
+            .insert_resource(ButtonInput::<KeyCode>::default())
+            .add_systems(Update, restart_system);
+
+        app.world.send_event(RestartEvent);
+        app.update();
+
+        // Level food follows the food rules like any other
+        let mut query = app.world.query_filtered::<&Expiry, With<Food>>();
+        assert_eq!(
+            query
+                .iter(&app.world)
+                .map(|expiry| expiry.duration())
+                .collect::<Vec<_>>(),
+            vec![Duration::from_secs(5)]
+        );
+        let mut query = app.world.query_filtered::<&Position, With<Food>>();
+        assert_eq!(
+            query.iter(&app.world).collect::<Vec<_>>(),
+            vec![&Position { x: 4, y: 0 }]
+        );
+        let mut query = app
+            .world
+            .query_filtered::<(&Position, &Player), With<Head>>();
+        let mut heads: Vec<(u8, Position)> = query
+            .iter(&app.world)
+            .map(|(position, player)| (player.id, position.clone()))
+            .collect();
+        heads.sort_unstable_by_key(|(id, _)| *id);
+        assert_eq!(
+            heads,
+            vec![(0, Position { x: 1, y: 1 }), (1, Position { x: 7, y: 1 })]
+        );
+    }
+
+    fn app_with_pause() -> App {
+        let mut app = app_in_play();
+        app.add_event::<RestartEvent>()
+            .add_event::<AppExit>()
+            .add_event::<WindowFocused>()
+            .add_systems(
+                Update,
+                (pause_input_system, focus_lost_system).run_if(in_state(AppState::Playing)),
+            )
+            .add_systems(OnEnter(AppState::Paused), pause_setup)
             .add_systems(
                 Update,
-                snake::movement_input_system.before(snake::movement_system),
+                paused_input_system.run_if(in_state(AppState::Paused)),
             )
-            .add_systems(Update, over_system.after(snake::movement_system)); // <--
+            .add_systems(OnExit(AppState::Paused), despawn_screen::<PauseScreen>);
+        app
+    }
 
-        // Add new input resource
+    fn press(app: &mut App, key: KeyCode) {
         let mut input = ButtonInput::<KeyCode>::default();
-        #[cfg(debug_assertions)]
-        input.press(KeyCode::KeyD);
-        #[cfg(not(debug_assertions))]
-        input.press(KeyCode::ArrowRight);
+        input.press(key);
         app.insert_resource(input);
+        app.update();
+        app.insert_resource(ButtonInput::<KeyCode>::default());
+        app.update();
+    }
 
-        // Run systems again
-        for _ in 0..7 {
-            app.update();
-        }
+    fn head_position(app: &mut App) -> Position {
+        let mut query = app
+            .world
+            .query_filtered::<(&Position, &Player), With<Head>>();
+        query
+            .iter(&app.world)
+            .find(|(_, player)| player.id == 0)
+            .map(|(position, _)| position.clone())
+            .unwrap()
+    }
+
+    #[test]
+    fn pause_freezes_the_round_until_resumed() {
+        // Setup
+        let mut app = app_with_pause();
+        app.insert_resource(ButtonInput::<KeyCode>::default());
+        app.update();
 
-        let mut query = app.world.query::<&GameEndEvent>();
+        press(&mut app, KeyCode::KeyP);
+        assert_eq!(state(&app), AppState::Paused);
+        let mut query = app.world.query_filtered::<Entity, With<PauseScreen>>();
         assert_eq!(query.iter(&app.world).count(), 1);
+
+        let frozen = head_position(&mut app);
+        app.update();
+        app.update();
+        assert_eq!(head_position(&mut app), frozen);
+
+        press(&mut app, KeyCode::Escape);
+        assert_eq!(state(&app), AppState::Playing);
+        assert_eq!(query.iter(&app.world).count(), 0);
+        assert_ne!(head_position(&mut app), frozen);
+    }
+
+    #[test]
+    fn gamepad_start_pauses() {
+        // Setup
+        let mut app = app_with_pause();
+        app.insert_resource(ButtonInput::<KeyCode>::default());
+        let mut buttons = ButtonInput::<GamepadButton>::default();
+        buttons.press(GamepadButton::new(
+            Gamepad::new(3),
+            GamepadButtonType::Start,
+        ));
+        app.insert_resource(buttons);
+        app.update();
+        app.update();
+
+        assert_eq!(state(&app), AppState::Paused);
+    }
+
+    #[test]
+    fn losing_window_focus_pauses() {
+        // Setup
+        let mut app = app_with_pause();
+        app.insert_resource(ButtonInput::<KeyCode>::default());
+        app.update();
+
+        app.world.send_event(WindowFocused {
+            window: Entity::PLACEHOLDER,
+            focused: false,
+        });
+        app.update();
+        app.update();
+
+        assert_eq!(state(&app), AppState::Paused);
+    }
+
+    #[test]
+    fn pause_menu_restarts_and_quits() {
+        // Setup
+        let mut app = app_with_pause();
+        app.insert_resource(ButtonInput::<KeyCode>::default());
+        app.update();
+        press(&mut app, KeyCode::Escape);
+
+        press(&mut app, KeyCode::KeyR);
+        assert_eq!(app.world.resource::<Events<RestartEvent>>().len(), 1);
+
+        press(&mut app, KeyCode::KeyQ);
+        assert_eq!(app.world.resource::<Events<AppExit>>().len(), 1);
+    }
+
+    #[test]
+    fn restart_key_sends_restart_event() {
+        // Setup
+        let mut app = App::new();
+        app.add_event::<RestartEvent>()
+            .add_systems(Update, restart_input_system);
+
+        let mut input = ButtonInput::<KeyCode>::default();
+        input.press(KeyCode::KeyR);
+        app.insert_resource(input);
+        app.update();
+
+        let events = app.world.resource::<Events<RestartEvent>>();
+        assert_eq!(events.len(), 1);
     }
 }
@@ -1,31 +1,89 @@
 use crate::components::{Position, Size};
 use bevy::{prelude::*, window::PrimaryWindow};
 
-#[cfg(debug_assertions)]
-pub(crate) const GRID_WIDTH: u16 = 10;
-#[cfg(not(debug_assertions))]
-pub(crate) const GRID_WIDTH: u16 = 20;
-#[cfg(debug_assertions)]
-pub(crate) const GRID_HEIGHT: u16 = 10;
-#[cfg(not(debug_assertions))]
-pub(crate) const GRID_HEIGHT: u16 = 20;
+const DEFAULT_GRID_WIDTH: u16 = 20;
+const DEFAULT_GRID_HEIGHT: u16 = 20;
+
+/// Board dimensions in cells, chosen at startup.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GridConfig {
+    pub width: u16,
+    pub height: u16,
+}
+
+impl Default for GridConfig {
+    fn default() -> Self {
+        Self::new(DEFAULT_GRID_WIDTH, DEFAULT_GRID_HEIGHT)
+    }
+}
+
+impl GridConfig {
+    #[must_use]
+    pub const fn new(width: u16, height: u16) -> Self {
+        Self { width, height }
+    }
+
+    #[must_use]
+    pub fn contains(&self, position: &Position) -> bool {
+        (0..i32::from(self.width)).contains(&i32::from(position.x))
+            && (0..i32::from(self.height)).contains(&i32::from(position.y))
+    }
+
+    /// Brings a position that left the board back in from the opposite edge.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn wrap(&self, position: &Position) -> Position {
+        Position {
+            x: i32::from(position.x).rem_euclid(i32::from(self.width)) as i16,
+            y: i32::from(position.y).rem_euclid(i32::from(self.height)) as i16,
+        }
+    }
+
+    #[must_use]
+    pub fn cells(&self) -> usize {
+        usize::from(self.width) * usize::from(self.height)
+    }
+
+    /// Every cell of the board, column by column.
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn positions(&self) -> impl Iterator<Item = Position> {
+        let height = self.height;
+        (0..self.width).flat_map(move |x| {
+            (0..height).map(move |y| Position {
+                x: x as i16,
+                y: y as i16,
+            })
+        })
+    }
+
+    /// Window size that keeps cells square, with the longest side `max_side` wide.
+    #[must_use]
+    pub fn window_size(&self, max_side: f32) -> (f32, f32) {
+        let tile_size = max_side / f32::from(self.width.max(self.height));
+        (
+            tile_size * f32::from(self.width),
+            tile_size * f32::from(self.height),
+        )
+    }
+}
 
 #[allow(clippy::missing_panics_doc)]
 #[allow(clippy::needless_pass_by_value)]
 pub fn size_scaling(
+    grid: Res<GridConfig>,
     primary_window: Query<&Window, With<PrimaryWindow>>,
     mut q: Query<(&Size, &mut Transform)>,
 ) {
     let window = primary_window.get_single().unwrap();
     for (sprite_size, mut transform) in &mut q.iter_mut() {
-        scale_sprite(transform.as_mut(), sprite_size, window);
+        scale_sprite(transform.as_mut(), sprite_size, window, *grid);
     }
 }
 
-fn scale_sprite(transform: &mut Transform, sprite_size: &Size, window: &Window) {
+fn scale_sprite(transform: &mut Transform, sprite_size: &Size, window: &Window, grid: GridConfig) {
     transform.scale = Vec3::new(
-        sprite_size.width / f32::from(GRID_WIDTH) * window.width(),
-        sprite_size.height / f32::from(GRID_HEIGHT) * window.height(),
+        sprite_size.width / f32::from(grid.width) * window.width(),
+        sprite_size.height / f32::from(grid.height) * window.height(),
         1.0,
     );
 }
@@ -33,12 +91,13 @@ fn scale_sprite(transform: &mut Transform, sprite_size: &Size, window: &Window)
 #[allow(clippy::missing_panics_doc)]
 #[allow(clippy::needless_pass_by_value)]
 pub fn position_translation(
+    grid: Res<GridConfig>,
     primary_window: Query<&Window, With<PrimaryWindow>>,
     mut q: Query<(&Position, &mut Transform)>,
 ) {
     let window = primary_window.get_single().unwrap();
     for (pos, mut transform) in &mut q.iter_mut() {
-        translate_position(transform.as_mut(), pos, window);
+        translate_position(transform.as_mut(), pos, window, *grid);
     }
 }
 
@@ -47,10 +106,15 @@ fn convert(pos: f32, bound_window: f32, grid_side_lenght: f32) -> f32 {
     (pos / grid_side_lenght).mul_add(bound_window, -bound_window / 2.) + (tile_size / 2.)
 }
 
-fn translate_position(transform: &mut Transform, pos: &Position, window: &Window) {
+fn translate_position(
+    transform: &mut Transform,
+    pos: &Position,
+    window: &Window,
+    grid: GridConfig,
+) {
     transform.translation = Vec3::new(
-        convert(f32::from(pos.x), window.width(), f32::from(GRID_WIDTH)),
-        convert(f32::from(pos.y), window.height(), f32::from(GRID_HEIGHT)),
+        convert(f32::from(pos.x), window.width(), f32::from(grid.width)),
+        convert(f32::from(pos.y), window.height(), f32::from(grid.height)),
         0.0,
     );
 }
@@ -66,16 +130,10 @@ mod test {
     #[test]
     fn transform_has_correct_scale_for_window() {
         // Setup
-        #[cfg(debug_assertions)] // <-- Debug
         let expected_transform = Transform {
             scale: Vec3::new(20., 20., 1.),
             ..default()
         };
-        #[cfg(not(debug_assertions))] // <-- Release
-        let expected_transform = Transform {
-            scale: Vec3::new(10., 10., 1.),
-            ..default()
-        };
         let mut default_transform = Transform {
             scale: Vec3::new(2., 3., 4.),
             ..default()
@@ -89,23 +147,27 @@ mod test {
         };
 
         // Apply scale
-        scale_sprite(&mut default_transform, &sprite_size, &window);
+        scale_sprite(
+            &mut default_transform,
+            &sprite_size,
+            &window,
+            GridConfig::new(10, 10),
+        );
 
         assert_eq!(default_transform, expected_transform);
     }
     #[test]
     fn convert_position_x_for_grid_width() {
-        let x = convert(4., 400., GRID_WIDTH as f32);
+        let x = convert(4., 400., 10.);
+        assert_relative_eq!(x, -20., epsilon = 0.00001);
 
-        #[cfg(debug_assertions)] // <-- DEBUG
-        assert_relative_eq!(x, -20., epsilon = 0.00001); // <-- DEBUG
-        #[cfg(not(debug_assertions))] // <-- RELEASE
-        assert_relative_eq!(x, -110., epsilon = 0.00001); // <-- RELEASE
+        let x = convert(4., 400., 20.);
+        assert_relative_eq!(x, -110., epsilon = 0.00001);
     }
 
     #[test]
     fn convert_position_y_for_grid_height() {
-        let y = convert(5., 400., GRID_HEIGHT as f32);
+        let y = convert(5., 400., 10.);
 
         assert_relative_eq!(y, 20., epsilon = 0.00001);
     }
@@ -114,10 +176,7 @@ mod test {
         let position = Position { x: 2, y: 8 };
         let mut default_transform = Transform::default();
         let expected = Transform {
-            #[cfg(debug_assertions)] // <-- DEBUG
-            translation: Vec3::new(-100., 140., 0.), // <-- DEBUG
-            #[cfg(not(debug_assertions))] // <-- RELEASE
-            translation: Vec3::new(-150., -29.999996, 0.), // <-- RELEASE
+            translation: Vec3::new(-100., 140., 0.),
             ..default()
         };
 
@@ -128,8 +187,54 @@ mod test {
         };
 
         // Apply translation
-        translate_position(&mut default_transform, &position, &window);
+        translate_position(
+            &mut default_transform,
+            &position,
+            &window,
+            GridConfig::new(10, 10),
+        );
 
         assert_eq!(default_transform, expected);
     }
+
+    #[test]
+    fn grid_contains_only_cells_inside_the_board() {
+        let grid = GridConfig::new(40, 30);
+
+        assert!(grid.contains(&Position { x: 0, y: 0 }));
+        assert!(grid.contains(&Position { x: 39, y: 29 }));
+        assert!(!grid.contains(&Position { x: 40, y: 0 }));
+        assert!(!grid.contains(&Position { x: 0, y: 30 }));
+        assert!(!grid.contains(&Position { x: -1, y: 5 }));
+        assert_eq!(grid.cells(), 1200);
+        assert_eq!(grid.positions().count(), grid.cells());
+        assert!(grid.positions().all(|position| grid.contains(&position)));
+    }
+
+    #[test]
+    fn wrap_brings_positions_back_from_the_opposite_edge() {
+        let grid = GridConfig::new(40, 30);
+
+        assert_eq!(
+            grid.wrap(&Position { x: -1, y: 5 }),
+            Position { x: 39, y: 5 }
+        );
+        assert_eq!(
+            grid.wrap(&Position { x: 40, y: 30 }),
+            Position { x: 0, y: 0 }
+        );
+        assert_eq!(
+            grid.wrap(&Position { x: 7, y: -1 }),
+            Position { x: 7, y: 29 }
+        );
+        assert_eq!(grid.wrap(&Position { x: 7, y: 8 }), Position { x: 7, y: 8 });
+    }
+
+    #[test]
+    fn window_size_keeps_cells_square() {
+        let (width, height) = GridConfig::new(40, 30).window_size(1000.);
+
+        assert_relative_eq!(width, 1000.);
+        assert_relative_eq!(height, 750.);
+    }
 }
@@ -0,0 +1,131 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::{
+    components::Player,
+    game::Rules,
+    score::{RoundTime, Scores},
+    snake::{self, Head, Segments},
+};
+
+const HUD_FONT_SIZE: f32 = 28.0;
+const HUD_TEXT_COLOR: Color = Color::rgb(0.9, 0.9, 0.9);
+/// Opacity of the lines of players knocked out of the round.
+const KNOCKED_OUT_ALPHA: f32 = 0.4;
+
+#[derive(Component)]
+pub struct Hud;
+
+pub fn setup(mut commands: Commands) {
+    commands
+        .spawn(
+            TextBundle::from_section("", hud_style(HUD_TEXT_COLOR)).with_style(Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.0),
+                left: Val::Px(10.0),
+                ..default()
+            }),
+        )
+        .insert(Hud);
+}
+
+/// Lists every player of the round, dimming the ones already knocked out.
+#[allow(clippy::needless_pass_by_value)]
+pub fn update_system(
+    rules: Res<Rules>,
+    scores: Res<Scores>,
+    segments: Res<Segments>,
+    round_time: Res<RoundTime>,
+    players: Query<&Player, With<Head>>,
+    mut hud: Query<&mut Text, With<Hud>>,
+) {
+    let playing: Vec<u8> = players.iter().map(|player| player.id).collect();
+
+    let mut sections: Vec<TextSection> = (0..rules.snakes())
+        .map(|player_id| {
+            let player = Player { id: player_id };
+            let color = snake::segment_color(player_id);
+            let (status, color) = if playing.contains(&player_id) {
+                (
+                    format!(
+                        "length {}",
+                        segments.get(player.id()).map_or(0, VecDeque::len)
+                    ),
+                    color,
+                )
+            } else {
+                ("out".to_string(), color.with_a(KNOCKED_OUT_ALPHA))
+            };
+            TextSection::new(
+                format!(
+                    "P{}  score {}  {status}\n",
+                    player.id() + 1,
+                    scores.get(player_id),
+                ),
+                hud_style(color),
+            )
+        })
+        .collect();
+    sections.push(TextSection::new(
+        format!("Time {:.1}s", round_time.elapsed_secs()),
+        hud_style(HUD_TEXT_COLOR),
+    ));
+
+    for mut text in &mut hud {
+        text.sections.clone_from(&sections);
+    }
+}
+
+fn hud_style(color: Color) -> TextStyle {
+    TextStyle {
+        font_size: HUD_FONT_SIZE,
+        color,
+        ..default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game::GameMode;
+
+    #[test]
+    fn knocked_out_players_stay_listed() {
+        // Setup
+        let mut app = App::new();
+        app.insert_resource(Rules {
+            mode: GameMode::Versus,
+            players: 3,
+            ..default()
+        })
+        .init_resource::<Scores>()
+        .init_resource::<Segments>()
+        .init_resource::<RoundTime>()
+        .add_systems(Update, update_system);
+        app.world.resource_mut::<Scores>().add(1, 20);
+        app.world.spawn((Player { id: 0 }, Head::default()));
+        app.world.spawn((Player { id: 2 }, Head::default()));
+        app.world.spawn((Text::default(), Hud));
+
+        app.update();
+
+        let mut query = app.world.query::<&Text>();
+        let text = query.single(&app.world);
+        let lines: Vec<&str> = text
+            .sections
+            .iter()
+            .map(|section| section.value.as_str())
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                "P1  score 0  length 0\n",
+                "P2  score 20  out\n",
+                "P3  score 0  length 0\n",
+                "Time 0.0s"
+            ]
+        );
+        assert_eq!(text.sections[1].style.color.a(), KNOCKED_OUT_ALPHA);
+    }
+}
@@ -0,0 +1,289 @@
+use std::{
+    fmt::{self, Display},
+    fs, io,
+    path::Path,
+};
+
+use bevy::prelude::*;
+
+use crate::{
+    components::{Position, Size},
+    game::MAX_PLAYERS,
+    grid::GridConfig,
+};
+
+const WALL_COLOR: Color = Color::rgb(0.35, 0.35, 0.4);
+const COMMENT: char = ';';
+
+#[derive(Component)]
+pub struct Wall;
+
+/// Arena layout. Levels are plain-text maps, one character per cell, with
+/// the first row at the top of the board:
+///
+/// ```text
+/// ; lines starting with a semicolon are comments
+/// ##########
+/// #1......2#
+/// #...##...#
+/// #...*....#
+/// ##########
+/// ```
+///
+/// `#` is a wall, `1` to `8` are the snake heads (their first segment goes
+/// in the cell below), `*` is food present when the round starts, and `.`
+/// or a space is empty floor. Rows shorter than the widest one are padded
+/// with floor.
+#[derive(Resource, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Level {
+    pub grid: Option<GridConfig>,
+    pub walls: Vec<Position>,
+    /// Head positions, indexed by player id.
+    pub spawns: Vec<Position>,
+    pub food: Vec<Position>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LevelError {
+    Io(String),
+    Empty,
+    UnknownCell {
+        row: usize,
+        column: usize,
+        cell: char,
+    },
+    DuplicateSpawn(u8),
+    /// Players have to be numbered 1, 2, 3... without gaps.
+    MissingSpawn(u8),
+    /// The snake's first segment would be off the board, or on a wall, food or
+    /// another snake.
+    BlockedSpawn(u8),
+    TooLarge,
+}
+
+impl Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LevelError::Io(err) => write!(f, "{err}"),
+            LevelError::Empty => write!(f, "level has no cells"),
+            LevelError::UnknownCell { row, column, cell } => {
+                write!(f, "unknown cell {cell:?} at row {row}, column {column}")
+            }
+            LevelError::DuplicateSpawn(player) => write!(f, "player {player} spawns twice"),
+            LevelError::MissingSpawn(player) => write!(f, "player {player} has no spawn"),
+            LevelError::BlockedSpawn(player) => {
+                write!(f, "player {player} has no room below its head")
+            }
+            LevelError::TooLarge => write!(f, "level is too large"),
+        }
+    }
+}
+
+impl std::error::Error for LevelError {}
+
+impl From<io::Error> for LevelError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err.to_string())
+    }
+}
+
+impl Level {
+    /// Whether `players` snakes each get a spawn point. Boards without a
+    /// level spread any number of snakes out themselves.
+    #[must_use]
+    pub fn seats(&self, players: u8) -> bool {
+        self.grid.is_none() || self.spawns.len() >= usize::from(players)
+    }
+
+    /// # Errors
+    ///
+    /// Returns a [`LevelError`] when the file cannot be read or parsed.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, LevelError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// # Errors
+    ///
+    /// Returns a [`LevelError`] when the map is empty, uses unknown cells or
+    /// places snakes where they cannot start.
+    pub fn parse(map: &str) -> Result<Self, LevelError> {
+        let rows: Vec<&str> = map
+            .lines()
+            .filter(|line| !line.trim_start().starts_with(COMMENT))
+            .map(str::trim_end)
+            .collect();
+        let rows = &rows[..rows
+            .iter()
+            .rposition(|row| !row.is_empty())
+            .map_or(0, |last| last + 1)];
+        let width = rows
+            .iter()
+            .map(|row| row.chars().count())
+            .max()
+            .unwrap_or(0);
+        if width == 0 {
+            return Err(LevelError::Empty);
+        }
+        let height = rows.len();
+        let to_i16 = |value: usize| i16::try_from(value).map_err(|_| LevelError::TooLarge);
+        let grid = GridConfig::new(
+            u16::try_from(width).map_err(|_| LevelError::TooLarge)?,
+            u16::try_from(height).map_err(|_| LevelError::TooLarge)?,
+        );
+
+        let mut level = Self {
+            grid: Some(grid),
+            ..default()
+        };
+        let mut spawns: Vec<Option<Position>> = vec![None; usize::from(MAX_PLAYERS)];
+        for (row, line) in rows.iter().enumerate() {
+            for (column, cell) in line.chars().enumerate() {
+                let position = Position {
+                    x: to_i16(column)?,
+                    y: to_i16(height - 1 - row)?,
+                };
+                match cell {
+                    '.' | ' ' => {}
+                    '#' => level.walls.push(position),
+                    '*' => level.food.push(position),
+                    '1'..='8' => {
+                        let player = cell as u8 - b'0';
+                        let spawn = &mut spawns[usize::from(player - 1)];
+                        if spawn.replace(position).is_some() {
+                            return Err(LevelError::DuplicateSpawn(player));
+                        }
+                    }
+                    _ => {
+                        return Err(LevelError::UnknownCell {
+                            row: row + 1,
+                            column: column + 1,
+                            cell,
+                        })
+                    }
+                }
+            }
+        }
+
+        let count = spawns
+            .iter()
+            .rposition(Option::is_some)
+            .map_or(0, |last| last + 1);
+        let heads = (1..=MAX_PLAYERS)
+            .zip(&spawns[..count])
+            .map(|(player, spawn)| spawn.clone().ok_or(LevelError::MissingSpawn(player)))
+            .collect::<Result<Vec<Position>, LevelError>>()?;
+        for (player, head) in (1..).zip(&heads) {
+            let first = Position {
+                x: head.x,
+                y: head.y - 1,
+            };
+            if !grid.contains(&first)
+                || level.walls.contains(&first)
+                || level.food.contains(&first)
+                || heads.contains(&first)
+            {
+                return Err(LevelError::BlockedSpawn(player));
+            }
+        }
+        level.spawns = heads;
+        Ok(level)
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+pub fn spawn_walls_system(mut commands: Commands, level: Res<Level>) {
+    for position in &level.walls {
+        commands
+            .spawn(SpriteBundle {
+                sprite: Sprite {
+                    color: WALL_COLOR,
+                    ..default()
+                },
+                ..default()
+            })
+            .insert(Wall)
+            .insert(position.clone())
+            .insert(Size::square(1.0));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const ARENA: &str = "\
+; small arena
+#######
+#1...2#
+#..#..#
+#.*...#
+#######
+";
+
+    #[test]
+    fn level_is_parsed_from_ascii_map() {
+        let level = Level::parse(ARENA).unwrap();
+
+        assert_eq!(level.grid, Some(GridConfig::new(7, 5)));
+        assert_eq!(
+            level.spawns,
+            vec![Position { x: 1, y: 3 }, Position { x: 5, y: 3 }]
+        );
+        assert_eq!(level.food, vec![Position { x: 2, y: 1 }]);
+        assert!(level.walls.contains(&Position { x: 3, y: 2 }));
+        assert!(level.walls.contains(&Position { x: 0, y: 0 }));
+        assert_eq!(level.walls.len(), 2 * 7 + 2 * 3 + 1);
+        assert!(level.seats(2));
+        assert!(!level.seats(3));
+        assert!(Level::default().seats(8));
+    }
+
+    #[test]
+    fn short_rows_are_padded_with_floor() {
+        let level = Level::parse("....\n.1\n..").unwrap();
+
+        assert_eq!(level.grid, Some(GridConfig::new(4, 3)));
+        assert_eq!(level.spawns, vec![Position { x: 1, y: 1 }]);
+    }
+
+    #[test]
+    fn invalid_levels_are_rejected() {
+        assert_eq!(Level::parse("; nothing\n"), Err(LevelError::Empty));
+        assert_eq!(
+            Level::parse("..\n.x"),
+            Err(LevelError::UnknownCell {
+                row: 2,
+                column: 2,
+                cell: 'x'
+            })
+        );
+        assert_eq!(Level::parse("1.1\n..."), Err(LevelError::DuplicateSpawn(1)));
+        assert_eq!(Level::parse("..2\n..."), Err(LevelError::MissingSpawn(1)));
+        assert_eq!(Level::parse("1..\n#.."), Err(LevelError::BlockedSpawn(1)));
+        assert_eq!(Level::parse("..1"), Err(LevelError::BlockedSpawn(1)));
+        assert_eq!(Level::parse("1..\n*.."), Err(LevelError::BlockedSpawn(1)));
+        assert_eq!(
+            Level::parse("1..\n2..\n..."),
+            Err(LevelError::BlockedSpawn(1))
+        );
+        assert_eq!(
+            Level::parse("2..\n1..\n..."),
+            Err(LevelError::BlockedSpawn(2))
+        );
+    }
+
+    #[test]
+    fn missing_file_is_an_io_error() {
+        assert!(matches!(
+            Level::load("does/not/exist.txt"),
+            Err(LevelError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn bundled_levels_parse() {
+        for path in ["levels/arena.txt", "levels/pillars.txt"] {
+            assert!(Level::load(path).is_ok(), "{path}");
+        }
+    }
+}
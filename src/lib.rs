@@ -0,0 +1,264 @@
+use bevy::{input::InputSystem, prelude::*};
+
+use components::GameEndEvent;
+use game::{AppState, CountdownScreen, GameOverScreen, MenuScreen, PauseScreen, RestartEvent};
+use hud::Hud;
+use settings::Settings;
+
+pub mod components;
+pub mod controls;
+pub mod food;
+pub mod game;
+pub mod grid;
+pub mod hud;
+pub mod level;
+pub mod occupancy;
+pub mod rng;
+pub mod score;
+pub mod settings;
+pub mod snake;
+pub mod speed;
+
+/// Resources, states and systems of the game itself. Windowing and drawing
+/// are left to the binary, so the whole game can also run headless.
+pub struct GamePlugin {
+    pub settings: Settings,
+}
+
+impl Plugin for GamePlugin {
+    fn build(&self, app: &mut App) {
+        let settings = &self.settings;
+        app.add_systems(Startup, level::spawn_walls_system)
+            .insert_resource(settings.grid)
+            .insert_resource(settings.controls.clone())
+            .insert_resource(settings.rules.clone())
+            .insert_resource(settings.food.clone())
+            .insert_resource(food::SpawnTimer::default())
+            .insert_resource(settings.level.clone())
+            .insert_resource(rng::GameRng::new(settings.seed))
+            .insert_resource(speed::TickRate::new(settings.difficulty, settings.ramp))
+            .insert_resource(speed::Ticks::default())
+            .insert_resource(snake::Segments::default())
+            .insert_resource(snake::LastTailPosition::default())
+            .insert_resource(occupancy::Occupancy::new(settings.grid))
+            .insert_resource(score::Scores::default())
+            .insert_resource(score::RoundTime::default())
+            .insert_resource(game::RoundOutcome::default())
+            .add_event::<GameEndEvent>()
+            .add_event::<snake::GrowthEvent>()
+            .add_event::<RestartEvent>()
+            .init_state::<AppState>()
+            .add_systems(
+                PreUpdate,
+                controls::gamepad_connection_system.after(InputSystem),
+            );
+        add_state_systems(app);
+        add_round_systems(app);
+    }
+}
+
+/// Screens and input of the menu, countdown, pause and game over states,
+/// and restarting into a new round.
+fn add_state_systems(app: &mut App) {
+    app.add_systems(
+        OnEnter(AppState::Menu),
+        (game::menu_setup, game::despawn_screen::<Hud>),
+    )
+    .add_systems(
+        Update,
+        game::menu_input_system.run_if(in_state(AppState::Menu)),
+    )
+    .add_systems(
+        OnExit(AppState::Menu),
+        (game::despawn_screen::<MenuScreen>, hud::setup),
+    )
+    .add_systems(
+        Update,
+        hud::update_system.run_if(not(in_state(AppState::Menu))),
+    )
+    .add_systems(OnEnter(AppState::Countdown), game::countdown_setup)
+    .add_systems(
+        Update,
+        game::countdown_system.run_if(in_state(AppState::Countdown)),
+    )
+    .add_systems(
+        OnExit(AppState::Countdown),
+        game::despawn_screen::<CountdownScreen>,
+    )
+    .add_systems(
+        Update,
+        (game::pause_input_system, game::focus_lost_system).run_if(in_state(AppState::Playing)),
+    )
+    .add_systems(OnEnter(AppState::Paused), game::pause_setup)
+    .add_systems(
+        Update,
+        game::paused_input_system.run_if(in_state(AppState::Paused)),
+    )
+    .add_systems(
+        OnExit(AppState::Paused),
+        game::despawn_screen::<PauseScreen>,
+    )
+    .add_systems(OnEnter(AppState::GameOver), game::game_over_setup)
+    .add_systems(
+        Update,
+        game::restart_input_system.run_if(in_state(AppState::GameOver)),
+    )
+    .add_systems(
+        Update,
+        // The occupancy is rebuilt with the new round rather than on entering
+        // the countdown, which a restart during the countdown does not do again
+        (
+            game::restart_system,
+            apply_deferred,
+            occupancy::rebuild_system,
+        )
+            .chain()
+            .run_if(on_event::<RestartEvent>()),
+    )
+    .add_systems(
+        OnExit(AppState::GameOver),
+        game::despawn_screen::<GameOverScreen>,
+    );
+}
+
+/// Steering, and the simulation stepped on every tick while playing.
+fn add_round_systems(app: &mut App) {
+    app.add_systems(
+        Update,
+        (
+            speed::pace_system,
+            snake::movement_input_system.run_if(in_state(AppState::Playing)),
+        ),
+    )
+    .add_systems(
+        FixedUpdate,
+        (
+            speed::tick_system,
+            snake::movement_system,
+            snake::eating_system,
+            snake::growth_system,
+            (
+                game::over_system,
+                score::score_system,
+                score::round_time_system,
+                (
+                    food::spawn_timer_system,
+                    food::spawn_system.run_if(food::spawn_due),
+                )
+                    .chain(),
+                food::expiry_system,
+            ),
+        )
+            .chain()
+            .run_if(in_state(AppState::Playing)),
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bevy::{
+        ecs::system::RunSystemOnce,
+        input::{
+            gamepad::{GamepadConnection, GamepadConnectionEvent, GamepadInfo},
+            InputPlugin,
+        },
+        window::WindowFocused,
+    };
+
+    use crate::{
+        components::Direction,
+        controls::{Controls, SteeringInput},
+        game::{GameMode, Rules},
+        grid::GridConfig,
+        occupancy::Occupancy,
+    };
+
+    /// The game as the binary runs it, minus the window.
+    fn headless_app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, InputPlugin))
+            .add_event::<WindowFocused>()
+            .add_plugins(GamePlugin {
+                settings: Settings {
+                    seed: Some(0),
+                    ..default()
+                },
+            });
+        app
+    }
+
+    fn connect(app: &mut App, id: usize) {
+        app.world.send_event(GamepadConnectionEvent::new(
+            Gamepad::new(id),
+            GamepadConnection::Connected(GamepadInfo { name: "pad".into() }),
+        ));
+    }
+
+    #[test]
+    fn connected_gamepads_are_handed_to_players() {
+        // Setup
+        let mut app = headless_app();
+        connect(&mut app, 3);
+
+        app.update();
+
+        let controls = app.world.resource::<Controls>();
+        assert_eq!(controls.player(0).unwrap().gamepad(), Some(Gamepad::new(3)));
+    }
+
+    #[test]
+    fn fifth_pad_steers_a_pad_only_player() {
+        // Setup
+        let mut app = headless_app();
+        for id in 0..5 {
+            connect(&mut app, id);
+        }
+        app.update();
+
+        // Players 1 to 4 took the first pads, player 5 has no keyboard layout
+        let controls = app.world.resource::<Controls>();
+        assert_eq!(controls.player(4).unwrap().gamepad(), Some(Gamepad::new(4)));
+        app.world
+            .resource_mut::<ButtonInput<GamepadButton>>()
+            .press(GamepadButton::new(
+                Gamepad::new(4),
+                GamepadButtonType::DPadLeft,
+            ));
+        let turns = app
+            .world
+            .run_system_once(|controls: Res<Controls>, input: SteeringInput| {
+                input.turns(controls.player(4).unwrap())
+            });
+
+        assert_eq!(turns, vec![Direction::Left]);
+    }
+
+    #[test]
+    fn restart_during_the_countdown_rebuilds_the_occupancy() {
+        // Setup
+        let mut app = headless_app();
+        app.world.resource_mut::<Rules>().mode = GameMode::Versus;
+        app.world.send_event(RestartEvent);
+        app.update();
+        app.update();
+        assert_eq!(
+            *app.world.resource::<State<AppState>>().get(),
+            AppState::Countdown
+        );
+
+        // One snake less, so the second one's cells must be freed
+        app.world.resource_mut::<Rules>().mode = GameMode::Classic;
+        app.world.send_event(RestartEvent);
+        app.update();
+        app.update();
+
+        let kept = app.world.resource::<Occupancy>().clone();
+        app.world.run_system_once(occupancy::rebuild_system);
+        let rebuilt = app.world.resource::<Occupancy>();
+        assert_eq!(kept.free_cells(), rebuilt.free_cells());
+        for position in app.world.resource::<GridConfig>().positions() {
+            assert_eq!(kept.get(&position), rebuilt.get(&position), "{position:?}");
+        }
+    }
+}
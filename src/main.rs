@@ -1,28 +1,22 @@
-use std::time::Duration;
+use bevy::prelude::*;
+use bevy_snake::{grid, rng::GameRng, settings::Settings, GamePlugin};
 
-use bevy::{prelude::*, time::common_conditions::on_timer};
-use components::GameEndEvent;
-
-pub mod components;
-pub mod food;
-pub mod game;
-pub mod grid;
-mod snake;
+const WINDOW_SIDE: f32 = 1000.0;
 
 fn main() {
-    App::new()
-        .add_systems(Startup, setup_camera)
-        .insert_resource(snake::Segments::default())
-        .insert_resource(snake::LastTailPosition::default())
-        .add_event::<GameEndEvent>() // <-- Adicionar
-        .add_event::<snake::GrowthEvent>()
-        .add_systems(Startup, snake::spawn_system)
-        .insert_resource(ClearColor(Color::rgb(0.04, 0.04, 0.04)))
+    let settings = Settings::from_args(std::env::args().skip(1)).unwrap_or_else(|err| {
+        eprintln!("{err}");
+        std::process::exit(2);
+    });
+    let window_size = settings.grid.window_size(WINDOW_SIDE);
+
+    let mut app = App::new();
+    app.insert_resource(ClearColor(Color::rgb(0.04, 0.04, 0.04)))
         .add_plugins(
             DefaultPlugins
                 .set(WindowPlugin {
                     primary_window: Some(Window {
-                        resolution: (1000.0, 1000.0).into(),
+                        resolution: window_size.into(),
                         title: "Snake".into(),
                         resizable: false,
                         ..default()
@@ -31,24 +25,11 @@ fn main() {
                 })
                 .build(),
         )
-        .add_systems(PostUpdate, (grid::position_translation, grid::size_scaling))
-        .add_systems(
-            Update,
-            food::spawn_system.run_if(on_timer(Duration::from_secs_f32(1.0))),
-        )
-        .add_systems(
-            Update,
-            snake::movement_system.run_if(on_timer(Duration::from_secs_f32(0.150))),
-        )
-        .add_systems(
-            Update,
-            snake::movement_input_system.before(snake::movement_system),
-        )
-        .add_systems(Update, snake::eating_system.after(snake::movement_system))
-        .add_systems(Update, snake::growth_system.after(snake::eating_system))
-        .add_systems(Update, game::over_system.after(snake::movement_system)) // <-- Adicionar
-        .add_systems(PostUpdate, (grid::position_translation, grid::size_scaling))
-        .run();
+        .add_plugins(GamePlugin { settings })
+        .add_systems(Startup, setup_camera)
+        .add_systems(PostUpdate, (grid::position_translation, grid::size_scaling));
+    println!("Seed: {}", app.world.resource::<GameRng>().seed());
+    app.run();
 }
 
 fn setup_camera(mut commands: Commands) {
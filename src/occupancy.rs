@@ -0,0 +1,226 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    components::{Player, Position},
+    food::Food,
+    grid::GridConfig,
+    level::Wall,
+    snake::{Head, Segment},
+};
+
+/// What can take up a cell. A cell holds several occupants for a moment when
+/// things collide, or when a snake that just grew has segments stacked up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Occupant {
+    Wall,
+    Food,
+    Head(u8),
+    Body(u8),
+}
+
+/// Cell by cell view of the board, kept up to date as snakes move and grow
+/// and food comes and goes, so collisions and free cells are looked up
+/// without scanning every entity.
+#[derive(Resource, Clone, Debug)]
+pub struct Occupancy {
+    grid: GridConfig,
+    cells: Vec<Vec<Occupant>>,
+    /// Indices of the empty cells, in no particular order.
+    free: Vec<usize>,
+    /// Where each empty cell sits in `free`.
+    free_slots: Vec<Option<usize>>,
+}
+
+/// Sized to the board's [`GridConfig`], or the default board without one.
+impl FromWorld for Occupancy {
+    fn from_world(world: &mut World) -> Self {
+        Self::new(
+            world
+                .get_resource::<GridConfig>()
+                .copied()
+                .unwrap_or_default(),
+        )
+    }
+}
+
+impl Occupancy {
+    #[must_use]
+    pub fn new(grid: GridConfig) -> Self {
+        let cells = grid.cells();
+        Self {
+            grid,
+            cells: vec![Vec::new(); cells],
+            free: (0..cells).collect(),
+            free_slots: (0..cells).map(Some).collect(),
+        }
+    }
+
+    /// Occupants of the cell, nothing for positions off the board.
+    #[must_use]
+    pub fn get(&self, position: &Position) -> &[Occupant] {
+        self.index(position)
+            .map_or(&[], |index| self.cells[index].as_slice())
+    }
+
+    #[must_use]
+    pub fn is_free(&self, position: &Position) -> bool {
+        self.index(position)
+            .is_some_and(|index| self.cells[index].is_empty())
+    }
+
+    #[must_use]
+    pub fn free_cells(&self) -> usize {
+        self.free.len()
+    }
+
+    /// A free cell picked uniformly at random.
+    pub fn random_free(&self, rng: &mut impl Rng) -> Option<Position> {
+        if self.free.is_empty() {
+            return None;
+        }
+        Some(self.position(self.free[rng.gen_range(0..self.free.len())]))
+    }
+
+    /// Puts `occupant` on the cell; positions off the board are ignored.
+    pub fn add(&mut self, position: &Position, occupant: Occupant) {
+        let Some(index) = self.index(position) else {
+            return;
+        };
+        if let Some(slot) = self.free_slots[index].take() {
+            self.free.swap_remove(slot);
+            if let Some(&moved) = self.free.get(slot) {
+                self.free_slots[moved] = Some(slot);
+            }
+        }
+        self.cells[index].push(occupant);
+    }
+
+    /// Takes one `occupant` off the cell, if it is there.
+    pub fn remove(&mut self, position: &Position, occupant: Occupant) {
+        let Some(index) = self.index(position) else {
+            return;
+        };
+        let cell = &mut self.cells[index];
+        if let Some(found) = cell.iter().position(|other| *other == occupant) {
+            cell.swap_remove(found);
+            if cell.is_empty() {
+                self.free_slots[index] = Some(self.free.len());
+                self.free.push(index);
+            }
+        }
+    }
+
+    pub fn move_occupant(&mut self, from: &Position, to: &Position, occupant: Occupant) {
+        self.remove(from, occupant);
+        self.add(to, occupant);
+    }
+
+    fn index(&self, position: &Position) -> Option<usize> {
+        self.grid.contains(position).then(|| {
+            usize::from(position.y.unsigned_abs()) * usize::from(self.grid.width)
+                + usize::from(position.x.unsigned_abs())
+        })
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_possible_wrap)]
+    fn position(&self, index: usize) -> Position {
+        let width = usize::from(self.grid.width);
+        Position {
+            x: (index % width) as i16,
+            y: (index / width) as i16,
+        }
+    }
+}
+
+/// Fills the occupancy from scratch with whatever is on the board, for the
+/// start of a round.
+#[allow(clippy::needless_pass_by_value)]
+#[allow(clippy::type_complexity)]
+pub fn rebuild_system(
+    mut commands: Commands,
+    grid: Res<GridConfig>,
+    walls: Query<&Position, With<Wall>>,
+    food: Query<&Position, With<Food>>,
+    segments: Query<(&Position, &Player, Has<Head>), With<Segment>>,
+) {
+    let mut occupancy = Occupancy::new(*grid);
+    for position in &walls {
+        occupancy.add(position, Occupant::Wall);
+    }
+    for position in &food {
+        occupancy.add(position, Occupant::Food);
+    }
+    for (position, player, head) in &segments {
+        let occupant = if head {
+            Occupant::Head(player.id)
+        } else {
+            Occupant::Body(player.id)
+        };
+        occupancy.add(position, occupant);
+    }
+    commands.insert_resource(occupancy);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rng::GameRng;
+
+    #[test]
+    fn cells_are_free_until_occupied() {
+        let mut occupancy = Occupancy::new(GridConfig::new(3, 2));
+        let cell = Position { x: 2, y: 1 };
+        assert_eq!(occupancy.free_cells(), 6);
+
+        occupancy.add(&cell, Occupant::Body(0));
+        occupancy.add(&cell, Occupant::Head(1));
+        assert!(!occupancy.is_free(&cell));
+        assert_eq!(
+            occupancy.get(&cell),
+            &[Occupant::Body(0), Occupant::Head(1)]
+        );
+        assert_eq!(occupancy.free_cells(), 5);
+
+        occupancy.remove(&cell, Occupant::Body(0));
+        occupancy.remove(&cell, Occupant::Food);
+        assert_eq!(occupancy.get(&cell), &[Occupant::Head(1)]);
+
+        occupancy.move_occupant(&cell, &Position { x: 0, y: 0 }, Occupant::Head(1));
+        assert!(occupancy.is_free(&cell));
+        assert_eq!(occupancy.free_cells(), 5);
+    }
+
+    #[test]
+    fn positions_off_the_board_are_never_free() {
+        let mut occupancy = Occupancy::new(GridConfig::new(3, 2));
+        let outside = Position { x: 3, y: 0 };
+
+        occupancy.add(&outside, Occupant::Wall);
+
+        assert!(!occupancy.is_free(&outside));
+        assert!(occupancy.get(&outside).is_empty());
+        assert_eq!(occupancy.free_cells(), 6);
+    }
+
+    #[test]
+    fn random_free_cell_is_the_only_one_left() {
+        let grid = GridConfig::new(4, 4);
+        let mut occupancy = Occupancy::new(grid);
+        let mut rng = GameRng::seeded(9);
+        for position in grid.positions() {
+            if position != (Position { x: 1, y: 2 }) {
+                occupancy.add(&position, Occupant::Wall);
+            }
+        }
+
+        assert_eq!(
+            occupancy.random_free(&mut *rng),
+            Some(Position { x: 1, y: 2 })
+        );
+
+        occupancy.add(&Position { x: 1, y: 2 }, Occupant::Food);
+        assert_eq!(occupancy.random_free(&mut *rng), None);
+    }
+}
@@ -0,0 +1,62 @@
+use bevy::prelude::*;
+use rand::{rngs::StdRng, SeedableRng};
+
+/// Source of every random decision in the game. Rounds started from the same
+/// seed play out the same way, so a seed can be shared as a challenge.
+#[derive(Resource, Deref, DerefMut)]
+pub struct GameRng {
+    seed: u64,
+    #[deref]
+    rng: StdRng,
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl GameRng {
+    /// Uses `seed`, or picks a fresh one when there is none.
+    #[must_use]
+    pub fn new(seed: Option<u64>) -> Self {
+        Self::seeded(seed.unwrap_or_else(rand::random))
+    }
+
+    #[must_use]
+    pub fn seeded(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    #[must_use]
+    pub const fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Rewinds to the start of the seed's sequence for a new round.
+    pub fn restart(&mut self) {
+        self.rng = StdRng::seed_from_u64(self.seed);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn same_seed_gives_same_sequence() {
+        let mut first = GameRng::seeded(42);
+        let mut second = GameRng::seeded(42);
+
+        let rolls: Vec<u32> = (0..8).map(|_| first.gen()).collect();
+        assert_eq!(rolls, (0..8).map(|_| second.gen()).collect::<Vec<u32>>());
+
+        first.restart();
+        assert_eq!(first.gen::<u32>(), rolls[0]);
+        assert_eq!(first.seed(), 42);
+    }
+}
@@ -0,0 +1,124 @@
+use bevy::{prelude::*, time::Stopwatch};
+
+use crate::{game::Rules, snake::GrowthEvent};
+
+/// Points scored by each player during the current round, indexed by player id.
+#[derive(Default, Resource, Debug, PartialEq, Eq)]
+pub struct Scores(Vec<u32>);
+
+impl Scores {
+    #[must_use]
+    pub fn get(&self, player_id: u8) -> u32 {
+        self.0.get(usize::from(player_id)).copied().unwrap_or(0)
+    }
+
+    pub fn add(&mut self, player_id: u8, points: u32) {
+        let index = usize::from(player_id);
+        if index >= self.0.len() {
+            self.0.resize(index + 1, 0);
+        }
+        self.0[index] += points;
+    }
+}
+
+/// Time spent in play since the round started; frozen outside `AppState::Playing`.
+#[derive(Default, Resource, Deref, DerefMut)]
+pub struct RoundTime(Stopwatch);
+
+#[allow(clippy::needless_pass_by_value)]
+pub fn score_system(
+    rules: Res<Rules>,
+    mut scores: ResMut<Scores>,
+    mut growth_reader: EventReader<GrowthEvent>,
+) {
+    for event in growth_reader.read() {
+        // Every segment gained is worth a food's points, poison costs nothing
+        let segments = u32::try_from(event.amount).unwrap_or(0);
+        scores.add(event.player_id, rules.mode.points_per_food() * segments);
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+pub fn round_time_system(time: Res<Time>, mut round_time: ResMut<RoundTime>) {
+    round_time.tick(time.delta());
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game::GameMode;
+
+    #[test]
+    fn unknown_player_has_no_score() {
+        let scores = Scores::default();
+
+        assert_eq!(scores.get(3), 0);
+    }
+
+    #[test]
+    fn growth_events_are_counted_per_player() {
+        // Setup
+        let mut app = App::new();
+        app.insert_resource(Scores::default())
+            .init_resource::<Rules>()
+            .add_event::<GrowthEvent>()
+            .add_systems(Update, score_system);
+
+        app.world.send_event(GrowthEvent {
+            player_id: 1,
+            amount: 1,
+        });
+        app.world.send_event(GrowthEvent {
+            player_id: 1,
+            amount: 1,
+        });
+        app.world.send_event(GrowthEvent {
+            player_id: 0,
+            amount: 1,
+        });
+        app.update();
+
+        let scores = app.world.resource::<Scores>();
+        assert_eq!(scores.get(0), 1);
+        assert_eq!(scores.get(1), 2);
+
+        // Events are only counted once
+        app.update();
+
+        let scores = app.world.resource::<Scores>();
+        assert_eq!(scores.get(1), 2);
+    }
+
+    #[test]
+    fn classic_mode_scores_ten_points_per_food() {
+        // Setup
+        let mut app = App::new();
+        app.insert_resource(Scores::default())
+            .insert_resource(Rules {
+                mode: GameMode::Classic,
+                ..default()
+            })
+            .add_event::<GrowthEvent>()
+            .add_systems(Update, score_system);
+
+        app.world.send_event(GrowthEvent {
+            player_id: 0,
+            amount: 1,
+        });
+        app.update();
+
+        assert_eq!(app.world.resource::<Scores>().get(0), 10);
+
+        app.world.send_event(GrowthEvent {
+            player_id: 0,
+            amount: 3,
+        });
+        app.world.send_event(GrowthEvent {
+            player_id: 0,
+            amount: -2,
+        });
+        app.update();
+
+        assert_eq!(app.world.resource::<Scores>().get(0), 40);
+    }
+}
@@ -0,0 +1,443 @@
+use std::{
+    fmt::{self, Display},
+    time::Duration,
+};
+
+use crate::{
+    controls::{parse_key_code, Controls, PlayerControls},
+    food::FoodRules,
+    game::{GameMode, HeadOnRule, Rules, MAX_PLAYERS, MIN_VERSUS_PLAYERS},
+    grid::GridConfig,
+    level::{Level, LevelError},
+    speed::Difficulty,
+};
+
+/// Smallest board that still fits two snakes side by side.
+const MIN_GRID_SIDE: u16 = 7;
+
+/// Startup options, read from the command line.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Settings {
+    pub grid: GridConfig,
+    pub controls: Controls,
+    pub rules: Rules,
+    pub food: FoodRules,
+    pub difficulty: Difficulty,
+    /// Speed the game up as the snakes grow.
+    pub ramp: bool,
+    /// Arena loaded with `--level`; the default is an empty board.
+    pub level: Level,
+    /// Fixed seed for the random number generator, a fresh one when unset.
+    pub seed: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SettingsError {
+    MissingValue(String),
+    InvalidValue {
+        option: String,
+        value: String,
+    },
+    UnknownOption(String),
+    /// The snakes would not each get a column of their own to start in.
+    BoardTooSmall {
+        players: u8,
+        grid: GridConfig,
+    },
+    InvalidLevel {
+        path: String,
+        reason: LevelError,
+    },
+    /// The level has fewer spawn points than there are snakes.
+    TooFewSpawns {
+        players: u8,
+        spawns: usize,
+    },
+    /// Versus was picked for fewer snakes than it takes.
+    TooFewPlayers(u8),
+}
+
+impl Display for SettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SettingsError::MissingValue(option) => write!(f, "missing value for {option}"),
+            SettingsError::InvalidValue { option, value } => {
+                write!(f, "invalid value {value:?} for {option}")
+            }
+            SettingsError::UnknownOption(option) => write!(f, "unknown option {option}"),
+            SettingsError::BoardTooSmall { players, grid } => write!(
+                f,
+                "a {}x{} board is too small for {players} players",
+                grid.width, grid.height
+            ),
+            SettingsError::InvalidLevel { path, reason } => {
+                write!(f, "cannot load level {path}: {reason}")
+            }
+            SettingsError::TooFewSpawns { players, spawns } => {
+                write!(f, "level only has {spawns} spawns for {players} players")
+            }
+            SettingsError::TooFewPlayers(players) => write!(
+                f,
+                "versus needs at least {MIN_VERSUS_PLAYERS} players, not {players}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SettingsError {}
+
+impl Settings {
+    /// Parses options such as `--grid 40x30` or `--keys 1=KeyZ,KeyQ,KeyS,KeyD`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`SettingsError`] for unknown options or malformed values.
+    pub fn from_args<I>(args: I) -> Result<Self, SettingsError>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut settings = Self::default();
+        let mut args = args.into_iter();
+        while let Some(option) = args.next() {
+            let value = args
+                .next()
+                .ok_or_else(|| SettingsError::MissingValue(option.clone()))?;
+            let invalid = || SettingsError::InvalidValue {
+                option: option.clone(),
+                value: value.clone(),
+            };
+            match option.as_str() {
+                "--grid" => settings.grid = parse_grid(&value).ok_or_else(invalid)?,
+                "--keys" => {
+                    let (player_id, controls) = parse_keys(&value).ok_or_else(invalid)?;
+                    settings.controls.set(player_id, controls);
+                }
+                "--difficulty" => {
+                    settings.difficulty = parse_difficulty(&value).ok_or_else(invalid)?;
+                }
+                "--ramp" => settings.ramp = parse_switch(&value).ok_or_else(invalid)?,
+                "--walls" => settings.rules.wrap_walls = parse_walls(&value).ok_or_else(invalid)?,
+                "--head-on" => {
+                    settings.rules.head_on = parse_head_on(&value).ok_or_else(invalid)?;
+                }
+                "--mode" => settings.rules.mode = parse_mode(&value).ok_or_else(invalid)?,
+                "--players" => {
+                    settings.rules.players = parse_players(&value).ok_or_else(invalid)?;
+                }
+                "--max-food" => {
+                    settings.food.max_food = parse_max_food(&value).ok_or_else(invalid)?;
+                }
+                "--food-interval" => {
+                    settings.food.interval = parse_seconds(&value).ok_or_else(invalid)?;
+                }
+                "--food-lifetime" => {
+                    settings.food.lifetime = match value.trim() {
+                        "off" => None,
+                        seconds => Some(parse_seconds(seconds).ok_or_else(invalid)?),
+                    };
+                }
+                "--seed" => settings.seed = Some(value.trim().parse().map_err(|_| invalid())?),
+                "--level" => {
+                    settings.level =
+                        Level::load(&value).map_err(|reason| SettingsError::InvalidLevel {
+                            path: value.clone(),
+                            reason,
+                        })?;
+                }
+                _ => return Err(SettingsError::UnknownOption(option)),
+            }
+        }
+        if settings.rules.mode == GameMode::Versus && settings.rules.players < MIN_VERSUS_PLAYERS {
+            return Err(SettingsError::TooFewPlayers(settings.rules.players));
+        }
+        if let Some(grid) = settings.level.grid {
+            // The level decides the board size and where every snake starts
+            settings.grid = grid;
+            let players = settings.rules.snakes();
+            if !settings.level.seats(players) {
+                return Err(SettingsError::TooFewSpawns {
+                    players,
+                    spawns: settings.level.spawns.len(),
+                });
+            }
+        } else if settings.grid.width < 2 * u16::from(settings.rules.players) + 1 {
+            return Err(SettingsError::BoardTooSmall {
+                players: settings.rules.players,
+                grid: settings.grid,
+            });
+        }
+        Ok(settings)
+    }
+}
+
+fn parse_grid(value: &str) -> Option<GridConfig> {
+    let (width, height) = value.split_once('x')?;
+    let grid = GridConfig::new(width.trim().parse().ok()?, height.trim().parse().ok()?);
+    (grid.width >= MIN_GRID_SIDE && grid.height >= MIN_GRID_SIDE).then_some(grid)
+}
+
+fn parse_difficulty(value: &str) -> Option<Difficulty> {
+    match value.trim() {
+        "easy" => Some(Difficulty::Easy),
+        "normal" => Some(Difficulty::Normal),
+        "hard" => Some(Difficulty::Hard),
+        "insane" => Some(Difficulty::Insane),
+        _ => None,
+    }
+}
+
+fn parse_switch(value: &str) -> Option<bool> {
+    match value.trim() {
+        "on" => Some(true),
+        "off" => Some(false),
+        _ => None,
+    }
+}
+
+fn parse_walls(value: &str) -> Option<bool> {
+    match value.trim() {
+        "solid" => Some(false),
+        "wrap" => Some(true),
+        _ => None,
+    }
+}
+
+fn parse_head_on(value: &str) -> Option<HeadOnRule> {
+    match value.trim() {
+        "both" => Some(HeadOnRule::BothDie),
+        "longer" => Some(HeadOnRule::LongerWins),
+        _ => None,
+    }
+}
+
+fn parse_mode(value: &str) -> Option<GameMode> {
+    match value.trim() {
+        "classic" => Some(GameMode::Classic),
+        "versus" => Some(GameMode::Versus),
+        _ => None,
+    }
+}
+
+fn parse_max_food(value: &str) -> Option<usize> {
+    value.trim().parse().ok().filter(|max_food| *max_food > 0)
+}
+
+/// Positive number of seconds, fractions allowed.
+fn parse_seconds(value: &str) -> Option<Duration> {
+    let seconds: f32 = value.trim().parse().ok()?;
+    (seconds > 0.0)
+        .then(|| Duration::try_from_secs_f32(seconds).ok())
+        .flatten()
+}
+
+fn parse_players(value: &str) -> Option<u8> {
+    let players = value.trim().parse().ok()?;
+    (1..=MAX_PLAYERS).contains(&players).then_some(players)
+}
+
+/// `<player>=<up>,<left>,<down>,<right>`, with players counted from 1.
+fn parse_keys(value: &str) -> Option<(u8, PlayerControls)> {
+    let (player, keys) = value.split_once('=')?;
+    let player_id = player.trim().parse::<u8>().ok()?.checked_sub(1)?;
+    let keys = keys
+        .split(',')
+        .map(|name| parse_key_code(name.trim()))
+        .collect::<Option<Vec<_>>>()?;
+    match keys[..] {
+        [up, left, down, right] => Some((player_id, PlayerControls::keys(up, left, down, right))),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bevy::input::keyboard::KeyCode;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn defaults_without_arguments() {
+        let settings = Settings::from_args(args(&[])).unwrap();
+
+        assert_eq!(settings.grid, GridConfig::default());
+        assert_eq!(settings.rules, Rules::default());
+    }
+
+    #[test]
+    fn grid_size_is_read_from_arguments() {
+        let settings = Settings::from_args(args(&["--grid", "40x30"])).unwrap();
+
+        assert_eq!(settings.grid, GridConfig::new(40, 30));
+    }
+
+    #[test]
+    fn invalid_grid_is_rejected() {
+        for value in ["40", "axb", "3x3", "40x"] {
+            assert_eq!(
+                Settings::from_args(args(&["--grid", value])),
+                Err(SettingsError::InvalidValue {
+                    option: "--grid".into(),
+                    value: value.into(),
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn keys_are_rebound_per_player() {
+        let settings = Settings::from_args(args(&["--keys", "1=KeyZ,KeyQ,KeyS,KeyD"])).unwrap();
+
+        assert_eq!(
+            settings.controls.player(0),
+            Some(&PlayerControls::keys(
+                KeyCode::KeyZ,
+                KeyCode::KeyQ,
+                KeyCode::KeyS,
+                KeyCode::KeyD
+            ))
+        );
+        assert_eq!(settings.controls.player(1), Controls::default().player(1));
+    }
+
+    #[test]
+    fn invalid_keys_are_rejected() {
+        for value in [
+            "0=KeyW,KeyA,KeyS,KeyD",
+            "1=KeyW,KeyA,KeyS",
+            "1=W,A,S,D",
+            "KeyW",
+        ] {
+            assert!(Settings::from_args(args(&["--keys", value])).is_err());
+        }
+    }
+
+    #[test]
+    fn player_count_is_read_and_checked_against_board() {
+        let settings = Settings::from_args(args(&["--players", "4"])).unwrap();
+        assert_eq!(settings.rules.players, 4);
+
+        for value in ["0", "9", "two"] {
+            assert!(Settings::from_args(args(&["--players", value])).is_err());
+        }
+        assert_eq!(
+            Settings::from_args(args(&["--mode", "versus", "--players", "1"])),
+            Err(SettingsError::TooFewPlayers(1))
+        );
+        let classic = Settings::from_args(args(&["--mode", "classic", "--players", "1"]));
+        assert_eq!(classic.unwrap().rules.players, 1);
+        assert_eq!(
+            Settings::from_args(args(&["--grid", "10x10", "--players", "5"])),
+            Err(SettingsError::BoardTooSmall {
+                players: 5,
+                grid: GridConfig::new(10, 10)
+            })
+        );
+    }
+
+    #[test]
+    fn mode_is_read_from_arguments() {
+        let settings = Settings::from_args(args(&["--mode", "classic"])).unwrap();
+        assert_eq!(settings.rules.mode, GameMode::Classic);
+        assert_eq!(settings.rules.snakes(), 1);
+
+        assert!(Settings::from_args(args(&["--mode", "coop"])).is_err());
+    }
+
+    #[test]
+    fn difficulty_and_ramp_are_read_from_arguments() {
+        let settings =
+            Settings::from_args(args(&["--difficulty", "insane", "--ramp", "on"])).unwrap();
+        assert_eq!(settings.difficulty, Difficulty::Insane);
+        assert!(settings.ramp);
+
+        assert!(Settings::from_args(args(&["--difficulty", "extreme"])).is_err());
+        assert!(Settings::from_args(args(&["--ramp", "yes"])).is_err());
+    }
+
+    #[test]
+    fn wrapping_walls_are_read_from_arguments() {
+        let settings = Settings::from_args(args(&["--walls", "wrap"])).unwrap();
+        assert!(settings.rules.wrap_walls);
+
+        assert!(Settings::from_args(args(&["--walls", "soft"])).is_err());
+    }
+
+    #[test]
+    fn food_rules_are_read_from_arguments() {
+        let settings = Settings::from_args(args(&[
+            "--max-food",
+            "5",
+            "--food-interval",
+            "0.5",
+            "--food-lifetime",
+            "10",
+        ]))
+        .unwrap();
+        assert_eq!(
+            settings.food,
+            FoodRules {
+                max_food: 5,
+                interval: Duration::from_millis(500),
+                lifetime: Some(Duration::from_secs(10)),
+            }
+        );
+
+        let settings = Settings::from_args(args(&["--food-lifetime", "off"])).unwrap();
+        assert_eq!(settings.food.lifetime, None);
+
+        assert!(Settings::from_args(args(&["--max-food", "0"])).is_err());
+        assert!(Settings::from_args(args(&["--food-interval", "-1"])).is_err());
+        assert!(Settings::from_args(args(&["--food-lifetime", "soon"])).is_err());
+    }
+
+    #[test]
+    fn head_on_rule_is_read_from_arguments() {
+        let settings = Settings::from_args(args(&["--head-on", "longer"])).unwrap();
+        assert_eq!(settings.rules.head_on, HeadOnRule::LongerWins);
+
+        assert!(Settings::from_args(args(&["--head-on", "nobody"])).is_err());
+    }
+
+    #[test]
+    fn seed_is_read_from_arguments() {
+        assert_eq!(Settings::from_args(args(&[])).unwrap().seed, None);
+        let settings = Settings::from_args(args(&["--seed", "20261018"])).unwrap();
+        assert_eq!(settings.seed, Some(20_261_018));
+
+        assert!(Settings::from_args(args(&["--seed", "-1"])).is_err());
+    }
+
+    #[test]
+    fn level_replaces_grid_and_spawns() {
+        let settings = Settings::from_args(args(&["--level", "levels/pillars.txt"])).unwrap();
+        assert_eq!(settings.grid, GridConfig::new(30, 20));
+        assert_eq!(settings.level.spawns.len(), 2);
+
+        assert_eq!(
+            Settings::from_args(args(&["--level", "levels/pillars.txt", "--players", "3"])),
+            Err(SettingsError::TooFewSpawns {
+                players: 3,
+                spawns: 2
+            })
+        );
+        assert!(matches!(
+            Settings::from_args(args(&["--level", "missing.txt"])),
+            Err(SettingsError::InvalidLevel { .. })
+        ));
+    }
+
+    #[test]
+    fn unknown_option_and_missing_value_are_rejected() {
+        assert_eq!(
+            Settings::from_args(args(&["--speed", "3"])),
+            Err(SettingsError::UnknownOption("--speed".into()))
+        );
+        assert_eq!(
+            Settings::from_args(args(&["--grid"])),
+            Err(SettingsError::MissingValue("--grid".into()))
+        );
+    }
+}
@@ -1,59 +1,204 @@
-use std::collections::HashMap;
+use std::collections::VecDeque;
 
 use crate::{
-    components::{Direction, GameEndEvent, Player, Position, Size},
+    components::{DeathCause, Direction, GameEndEvent, Player, Position, Size},
+    controls::{Controls, SteeringInput},
     food::Food,
-    grid::{GRID_HEIGHT, GRID_WIDTH},
+    game::{HeadOnRule, Rules},
+    grid::GridConfig,
+    level::Level,
+    occupancy::{Occupancy, Occupant},
+    speed::TickRate,
 };
 use bevy::prelude::*;
 
 const SNAKE_HEAD_COLOR: Color = Color::rgb(0.7, 0.7, 0.7);
-const SNAKE1_SEGMENT_COLOR: Color = Color::rgb(0.8, 0.0, 0.8); // <--
-const SNAKE2_SEGMENT_COLOR: Color = Color::rgb(0., 0.8, 0.8); // <--
-
+const SEGMENT_COLORS: [Color; 8] = [
+    Color::rgb(0.8, 0.0, 0.8),
+    Color::rgb(0., 0.8, 0.8),
+    Color::rgb(0.9, 0.6, 0.1),
+    Color::rgb(0.3, 0.8, 0.2),
+    Color::rgb(0.9, 0.2, 0.2),
+    Color::rgb(0.3, 0.4, 0.95),
+    Color::rgb(0.95, 0.9, 0.2),
+    Color::rgb(0.6, 0.4, 0.3),
+];
+/// Row the snake heads start on, with their first segment just below.
+const SPAWN_ROW: i16 = 3;
+/// Poison never shrinks a snake below its head and one segment.
+const MIN_LENGTH: usize = 2;
+/// Turns buffered ahead of the snake; extra presses are dropped.
+const MAX_QUEUED_TURNS: usize = 3;
+
+/// Front of a snake. Turns are queued as they are pressed and taken one per
+/// move, so quick presses between two moves are neither lost nor able to
+/// reverse the snake onto itself.
 #[derive(Component)]
 pub struct Head {
+    /// Direction of the last move.
     direction: Direction,
+    turns: VecDeque<Direction>,
+    /// Whether the food on the cell of the last move is this snake's to eat.
+    eats: bool,
+    /// Whether the last move onto food already grew the first new segment.
+    grew: bool,
 }
 
-#[derive(Component)]
-pub struct Segment;
+/// Part of a snake. Every segment also carries its owner's [`Player`].
+///
+/// A move only touches the head and the tail, so rather than a place that
+/// would change on every segment each tick, segments keep a stamp: the head
+/// counts its moves, and each body segment keeps the count the head had when
+/// it was in the segment's cell. A segment's place in the snake therefore
+/// needs its head's stamp too, see [`Segment::place`].
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Segment {
+    pub stamp: i64,
+}
+
+impl Segment {
+    /// Place in the snake behind `head`, counted from the head at 0, or
+    /// nothing when the stamp is ahead of the head's, which no segment of
+    /// that snake can have.
+    #[must_use]
+    pub fn place(&self, head: &Segment) -> Option<usize> {
+        usize::try_from(head.stamp - self.stamp).ok()
+    }
+
+    /// Stamp of the segment at `place` behind `head`.
+    #[must_use]
+    #[allow(clippy::cast_possible_wrap)]
+    pub const fn behind(head: &Segment, place: usize) -> Self {
+        Self {
+            stamp: head.stamp - place as i64,
+        }
+    }
+}
 
+/// Entities of every snake, head first, indexed by player id.
 #[derive(Default, Deref, DerefMut, Resource)]
-pub struct Segments([Vec<Entity>; 2]);
+pub struct Segments(Vec<VecDeque<Entity>>);
 
 #[derive(Event)]
 pub struct GrowthEvent {
     pub player_id: u8,
+    /// Segments to add, or to remove when negative.
+    pub amount: i16,
 }
 
+/// Cell each snake's tail left on its last move, or still holds when the
+/// snake grew instead, indexed by player id. New segments grow there.
 #[derive(Default, Resource)]
-pub struct LastTailPosition(Option<Position>);
+pub struct LastTailPosition(Vec<Option<Position>>);
+
+impl LastTailPosition {
+    #[must_use]
+    pub fn get(&self, player_id: u8) -> Option<&Position> {
+        self.0.get(usize::from(player_id))?.as_ref()
+    }
+
+    pub fn set(&mut self, player_id: u8, position: Position) {
+        let index = usize::from(player_id);
+        if index >= self.0.len() {
+            self.0.resize(index + 1, None);
+        }
+        self.0[index] = Some(position);
+    }
+}
 
 impl Default for Head {
     fn default() -> Self {
         Self {
             direction: Direction::Up,
+            turns: VecDeque::new(),
+            eats: false,
+            grew: false,
         }
     }
 }
 
-pub fn spawn_system(mut commands: Commands, mut segments: ResMut<Segments>) {
-    *segments = Segments([
-        spawn_entity_with_segment(&mut commands, 0),
-        spawn_entity_with_segment(&mut commands, 1),
-    ]);
+impl Head {
+    #[must_use]
+    pub const fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    /// Buffers a turn unless it repeats or reverses the one before it.
+    pub fn queue_turn(&mut self, turn: Direction) {
+        let last = self.turns.back().copied().unwrap_or(self.direction);
+        if turn != last && turn != last.opposite() && self.turns.len() < MAX_QUEUED_TURNS {
+            self.turns.push_back(turn);
+        }
+    }
+
+    /// Takes the next queued turn for this move and returns the direction to go.
+    fn advance(&mut self) -> Direction {
+        while let Some(turn) = self.turns.pop_front() {
+            if turn != self.direction.opposite() {
+                self.direction = turn;
+                break;
+            }
+        }
+        self.direction
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+pub fn spawn_system(
+    mut commands: Commands,
+    grid: Res<GridConfig>,
+    level: Res<Level>,
+    rules: Res<Rules>,
+    mut segments: ResMut<Segments>,
+) {
+    *segments = spawn_snakes(&mut commands, &grid, &level, rules.snakes());
+}
+
+/// Spawns `players` snakes at the level's spawn points, or spread across the
+/// board when the level does not place them.
+pub fn spawn_snakes(
+    commands: &mut Commands,
+    grid: &GridConfig,
+    level: &Level,
+    players: u8,
+) -> Segments {
+    Segments(
+        (0..players)
+            .map(|player_id| {
+                let head = level
+                    .spawns
+                    .get(usize::from(player_id))
+                    .cloned()
+                    .unwrap_or_else(|| spawn_position(grid, player_id, players));
+                spawn_entity_with_segment(commands, head, player_id)
+            })
+            .collect(),
+    )
+}
+
+/// Head position of `player_id`, spreading `players` snakes evenly across the board.
+#[must_use]
+#[allow(clippy::cast_possible_truncation)]
+#[allow(clippy::cast_possible_wrap)]
+pub fn spawn_position(grid: &GridConfig, player_id: u8, players: u8) -> Position {
+    let slots = 2 * (u32::from(players) + 1);
+    let x = (2 * (u32::from(player_id) + 1) * u32::from(grid.width) + slots / 2) / slots;
+    Position {
+        x: x as i16,
+        y: SPAWN_ROW,
+    }
 }
 
-pub fn spawn_segment_system(commands: &mut Commands, position: Position, player_id: u8) -> Entity {
+pub fn spawn_segment_system(
+    commands: &mut Commands,
+    position: Position,
+    player_id: u8,
+    segment: Segment,
+) -> Entity {
     commands
         .spawn(SpriteBundle {
             sprite: Sprite {
-                color: if player_id == 0 {
-                    SNAKE1_SEGMENT_COLOR
-                } else {
-                    SNAKE2_SEGMENT_COLOR
-                },
+                color: segment_color(player_id),
                 ..default()
             },
             transform: Transform {
@@ -62,133 +207,251 @@ pub fn spawn_segment_system(commands: &mut Commands, position: Position, player_
             },
             ..default()
         })
-        .insert(Segment)
+        .insert(segment)
+        .insert(Player { id: player_id })
         .insert(position)
         .insert(Size::square(0.65))
         .id()
 }
 
+#[must_use]
+pub fn segment_color(player_id: u8) -> Color {
+    SEGMENT_COLORS[usize::from(player_id) % SEGMENT_COLORS.len()]
+}
+
 #[allow(clippy::needless_pass_by_value)]
 pub fn movement_input_system(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    controls: Res<Controls>,
+    input: SteeringInput,
     mut heads: Query<(&mut Head, &Player)>,
 ) {
     heads.iter_mut().for_each(|(mut head, player)| {
-        let dir: Direction = if player.id() == 0 {
-            if keyboard_input.pressed(KeyCode::KeyA) {
-                Direction::Left
-            } else if keyboard_input.pressed(KeyCode::KeyS) {
-                Direction::Down
-            } else if keyboard_input.pressed(KeyCode::KeyW) {
-                Direction::Up
-            } else if keyboard_input.pressed(KeyCode::KeyD) {
-                Direction::Right
-            } else {
-                head.direction
+        if let Some(player_controls) = controls.player(player.id) {
+            for turn in input.turns(player_controls) {
+                head.queue_turn(turn);
             }
-        } else if player.id() == 1 {
-            if keyboard_input.pressed(KeyCode::ArrowLeft) {
-                Direction::Left
-            } else if keyboard_input.pressed(KeyCode::ArrowDown) {
-                Direction::Down
-            } else if keyboard_input.pressed(KeyCode::ArrowUp) {
-                Direction::Up
-            } else if keyboard_input.pressed(KeyCode::ArrowRight) {
-                Direction::Right
-            } else {
-                head.direction
-            }
-        } else {
-            head.direction
-        };
-        if dir != head.direction.opposite() {
-            head.direction = dir;
         }
     });
 }
 
+/// Moves every snake one cell, then checks each head against where
+/// everything ended up: a tail that moved away this tick frees its cell.
+/// A snake moving onto food that grows it keeps its tail in place and gains
+/// a segment behind the head instead, so the tail never frees a cell for
+/// another head on the tick it grows.
+/// Heads meeting or swapping places are settled by [`Rules::head_on`], so the
+/// outcome never depends on which snake moved first.
 #[allow(clippy::needless_pass_by_value)]
-#[allow(clippy::cast_sign_loss)]
+#[allow(clippy::too_many_arguments)]
 pub fn movement_system(
-    segments: ResMut<Segments>,
+    mut commands: Commands,
+    grid: Res<GridConfig>,
+    rules: Res<Rules>,
+    mut segments: ResMut<Segments>,
+    mut occupancy: ResMut<Occupancy>,
     mut last_tail_position: ResMut<LastTailPosition>,
     mut game_end_writer: EventWriter<GameEndEvent>, // <-- Adicionar EventWriter
-    heads: Query<(Entity, &Head, &Player)>,
-    mut positions: Query<(Entity, &Segment, &mut Position)>,
-    game_end: Query<&GameEndEvent>, // <--Adicionar
+    mut heads: Query<(Entity, &mut Head, &Player)>,
+    mut positions: Query<(&mut Position, &mut Segment)>,
+    food: Query<(&Position, &Food), Without<Segment>>,
 ) {
-    let positions_clone: HashMap<Entity, Position> = positions
-        .iter()
-        .map(|(entity, _segment, position)| (entity, position.clone()))
-        .collect();
-    for (entity_id, head, Player { id }) in heads.iter() {
-        let player_id = (*id) as usize;
-        (*segments[player_id]).windows(2).for_each(|entity| {
-            if let Ok((_, _segment, mut position)) = positions.get_mut(entity[1]) {
-                if let Some(new_position) = positions_clone.get(&entity[0]) {
-                    *position = new_position.clone();
-                }
-            };
+    // Where every head goes, decided before anything moves
+    let mut targets: Vec<(Entity, u8, Position)> = Vec::with_capacity(segments.len());
+    for (entity_id, mut head, Player { id }) in &mut heads {
+        let direction = head.advance();
+        let Ok((pos, _)) = positions.get(entity_id) else {
+            continue;
+        };
+        let mut to = pos.clone();
+        match direction {
+            Direction::Left => {
+                to.x -= 1;
+            }
+            Direction::Right => {
+                to.x += 1;
+            }
+            Direction::Up => {
+                to.y += 1;
+            }
+            Direction::Down => {
+                to.y -= 1;
+            }
+        }
+        if rules.wrap_walls {
+            to = grid.wrap(&to);
+        }
+        targets.push((entity_id, *id, to));
+    }
+    let eaters = food_eaters(&targets, &occupancy, &segments);
+
+    let mut moved: Vec<MovedHead> = Vec::with_capacity(targets.len());
+    for (entity_id, id, to) in targets {
+        let Ok((_, mut head, _)) = heads.get_mut(entity_id) else {
+            continue;
+        };
+        let Some(snake) = segments.get_mut(usize::from(id)) else {
+            continue;
+        };
+        let Ok((mut pos, mut segment)) = positions.get_mut(entity_id) else {
+            continue;
+        };
+        let from = std::mem::replace(&mut *pos, to.clone());
+        let neck = *segment;
+        segment.stamp += 1;
+        occupancy.move_occupant(&from, &to, Occupant::Head(id));
+        head.eats = eaters.contains(&id);
+        head.grew = head.eats
+            && food
+                .iter()
+                .any(|(position, food)| *position == to && food.kind.growth() > 0);
+
+        // The rest of the body stays put, only the tail moves up behind the
+        // head, or stays where it is while a new segment fills in for it
+        let mut tail = from.clone();
+        if head.grew {
+            if let Some((position, _)) = snake.back().and_then(|tail| positions.get(*tail).ok()) {
+                tail = position.clone();
+            }
+            occupancy.add(&from, Occupant::Body(id));
+            snake.insert(
+                1,
+                spawn_segment_system(&mut commands, from.clone(), id, neck),
+            );
+        } else if let Some(&tail_entity) = snake.back().filter(|_| snake.len() > 1) {
+            if let Ok((mut position, mut segment)) = positions.get_mut(tail_entity) {
+                tail = std::mem::replace(&mut *position, from.clone());
+                *segment = neck;
+                snake.pop_back();
+                snake.insert(1, tail_entity);
+                occupancy.move_occupant(&tail, &from, Occupant::Body(id));
+            }
+        }
+        last_tail_position.set(id, tail);
+        moved.push(MovedHead {
+            player_id: id,
+            from,
+            to,
         });
+    }
 
-        if game_end.is_empty() {
-            // <-- if verificando se houve um evento de fimd e jogo
-
-            let _ = positions.get_mut(entity_id).map(|(_, _segment, mut pos)| {
-                match &head.direction {
-                    Direction::Left => {
-                        pos.x -= 1;
-                    }
-                    Direction::Right => {
-                        pos.x += 1;
-                    }
-                    Direction::Up => {
-                        pos.y += 1;
-                    }
-                    Direction::Down => {
-                        pos.y -= 1;
-                    }
-                };
-                if pos.x < 0
-                    || pos.y < 0
-                    || pos.x as u16 >= GRID_WIDTH
-                    || pos.y as u16 >= GRID_HEIGHT
-                {
-                    game_end_writer.send(GameEndEvent::GameOver); // <-- publicar evento
-                }
+    for head in &moved {
+        if let Some(cause) = crash_cause(head, &moved, *grid, &rules, &occupancy, &segments) {
+            game_end_writer.send(GameEndEvent::Crashed {
+                player_id: head.player_id,
+                cause,
+            }); // <-- publicar evento
+        }
+    }
+}
 
-                if positions_clone
-                    .iter()
-                    .filter(|(k, _)| k != &&entity_id)
-                    .map(|(_, v)| v)
-                    .any(|segment_position| &*pos == segment_position)
-                {
-                    game_end_writer.send(GameEndEvent::GameOver);
-                }
-            });
+/// Players whose head takes the food on the cell it moves onto. Heads racing
+/// for the same food leave it to the longest snake, or to nobody on a tie.
+fn food_eaters(
+    targets: &[(Entity, u8, Position)],
+    occupancy: &Occupancy,
+    segments: &Segments,
+) -> Vec<u8> {
+    let length = |player_id: u8| {
+        segments
+            .get(usize::from(player_id))
+            .map_or(0, VecDeque::len)
+    };
+    targets
+        .iter()
+        .filter(|(_, _, to)| occupancy.get(to).contains(&Occupant::Food))
+        .filter(|(_, id, to)| {
+            targets
+                .iter()
+                .filter(|(_, other, other_to)| other != id && other_to == to)
+                .all(|(_, other, _)| length(*other) < length(*id))
+        })
+        .map(|(_, id, _)| *id)
+        .collect()
+}
+
+/// What `head` crashed into once every snake has moved, if anything.
+fn crash_cause(
+    head: &MovedHead,
+    moved: &[MovedHead],
+    grid: GridConfig,
+    rules: &Rules,
+    occupancy: &Occupancy,
+    segments: &Segments,
+) -> Option<DeathCause> {
+    let length = |player_id: u8| {
+        segments
+            .get(usize::from(player_id))
+            .map_or(0, VecDeque::len)
+    };
+    let pos = &head.to;
+    // Heads meeting in one cell, or swapping cells, collide head-on
+    let head_on: Vec<u8> = moved
+        .iter()
+        .filter(|other| other.player_id != head.player_id)
+        .filter(|other| other.to == *pos || (other.to == head.from && other.from == *pos))
+        .map(|other| other.player_id)
+        .collect();
+    let occupants = occupancy.get(pos);
+    let body_hit = occupants.iter().find_map(|occupant| match occupant {
+        Occupant::Body(owner) if !head_on.contains(owner) => Some(*owner),
+        _ => None,
+    });
+    if !grid.contains(pos) || occupants.contains(&Occupant::Wall) {
+        Some(DeathCause::Wall)
+    } else if let Some(owner) = body_hit {
+        if owner == head.player_id {
+            Some(DeathCause::OwnBody)
+        } else {
+            Some(DeathCause::OpponentBody)
+        }
+    } else if head_on.is_empty() {
+        None
+    } else {
+        match rules.head_on {
+            HeadOnRule::BothDie => Some(DeathCause::HeadOn),
+            HeadOnRule::LongerWins => head_on
+                .iter()
+                .any(|other| length(*other) >= length(head.player_id))
+                .then_some(DeathCause::HeadOn),
         }
-        *last_tail_position = LastTailPosition(Some(
-            positions_clone
-                .get(segments[player_id].last().unwrap())
-                .unwrap()
-                .clone(),
-        ));
     }
 }
 
+/// Where a head went on this tick's move.
+struct MovedHead {
+    player_id: u8,
+    from: Position,
+    to: Position,
+}
+
 #[allow(clippy::needless_pass_by_value)]
 pub fn eating_system(
     mut commands: Commands,
+    mut tick_rate: ResMut<TickRate>,
+    mut occupancy: ResMut<Occupancy>,
     mut growth_writer: EventWriter<GrowthEvent>,
-    food_positions: Query<(Entity, &Position), With<Food>>,
-    head_positions: Query<(&Position, &Player), With<Head>>,
+    food_positions: Query<(Entity, &Position, &Food)>,
+    head_positions: Query<(&Position, &Player, &Head)>,
 ) {
-    for (head_pos, Player { id }) in head_positions.iter() {
-        for (ent, food_pos) in food_positions.iter() {
-            if food_pos == head_pos {
-                commands.entity(ent).despawn();
-                growth_writer.send(GrowthEvent { player_id: *id });
-            }
+    for (head_pos, Player { id }, head) in &head_positions {
+        // Each food goes once, to the head the move picked for it
+        if !head.eats || !occupancy.get(head_pos).contains(&Occupant::Food) {
+            continue;
+        }
+        let Some((ent, food_pos, food)) = food_positions
+            .iter()
+            .find(|(_, food_pos, _)| *food_pos == head_pos)
+        else {
+            continue;
+        };
+        occupancy.remove(food_pos, Occupant::Food);
+        commands.entity(ent).despawn();
+        growth_writer.send(GrowthEvent {
+            player_id: *id,
+            amount: food.kind.growth(),
+        });
+        if let Some((percent, duration)) = food.kind.pace() {
+            tick_rate.modify(percent, duration);
         }
     }
 }
@@ -198,23 +461,73 @@ pub fn growth_system(
     mut commands: Commands,
     last_tail_position: Res<LastTailPosition>,
     mut segments: ResMut<Segments>,
+    mut occupancy: ResMut<Occupancy>,
     mut growth_reader: EventReader<GrowthEvent>,
+    mut heads: Query<&mut Head>,
+    positions: Query<(&Position, &Segment)>,
 ) {
     growth_reader.read().for_each(|event| {
-        let player_id = event.player_id as usize;
-        if player_id < segments.len() {
-            segments[player_id].push(spawn_segment_system(
-                &mut commands,
-                last_tail_position.0.clone().unwrap(),
-                event.player_id,
-            ));
+        let Some(snake) = segments.get_mut(event.player_id as usize) else {
+            return;
+        };
+        if event.amount < 0 {
+            let length = snake
+                .len()
+                .saturating_sub(usize::from(event.amount.unsigned_abs()))
+                .max(MIN_LENGTH);
+            for entity in snake.drain(length.min(snake.len())..) {
+                if let Ok((position, _)) = positions.get(entity) {
+                    occupancy.remove(position, Occupant::Body(event.player_id));
+                }
+                commands.entity(entity).despawn();
+            }
+        } else {
+            // Before the first move there is no vacated cell, grow onto the tail
+            let Some(tail) = last_tail_position
+                .get(event.player_id)
+                .cloned()
+                .or_else(|| {
+                    snake
+                        .back()
+                        .and_then(|tail| positions.get(*tail).ok())
+                        .map(|(position, _)| position.clone())
+                })
+            else {
+                return;
+            };
+            let Some((_, head)) = snake.front().and_then(|head| positions.get(*head).ok()) else {
+                return;
+            };
+            // The move onto the food already grew the first segment
+            let grown = snake
+                .front()
+                .and_then(|head| heads.get_mut(*head).ok())
+                .is_some_and(|mut head| std::mem::take(&mut head.grew));
+            for _ in i16::from(grown)..event.amount {
+                let segment = Segment::behind(head, snake.len());
+                occupancy.add(&tail, Occupant::Body(event.player_id));
+                snake.push_back(spawn_segment_system(
+                    &mut commands,
+                    tail.clone(),
+                    event.player_id,
+                    segment,
+                ));
+            }
         }
     });
 }
 
-#[allow(clippy::cast_possible_wrap)]
-fn spawn_entity_with_segment(commands: &mut Commands, player_id: u8) -> Vec<Entity> {
-    vec![
+fn spawn_entity_with_segment(
+    commands: &mut Commands,
+    head: Position,
+    player_id: u8,
+) -> VecDeque<Entity> {
+    let tail = Position {
+        x: head.x,
+        y: head.y - 1,
+    };
+    let first = Segment { stamp: 0 };
+    VecDeque::from([
         commands
             .spawn(SpriteBundle {
                 sprite: Sprite {
@@ -229,38 +542,26 @@ fn spawn_entity_with_segment(commands: &mut Commands, player_id: u8) -> Vec<Enti
             })
             .insert(Player { id: player_id })
             .insert(Head::default())
-            .insert(Segment)
-            .insert(Position {
-                x: if player_id == 0 {
-                    3
-                } else {
-                    (GRID_WIDTH - 3) as i16
-                },
-                y: 3,
-            })
+            .insert(first)
+            .insert(head)
             .insert(Size::square(0.8))
             .id(),
-        spawn_segment_system(
-            commands,
-            Position {
-                x: if player_id == 0 {
-                    3
-                } else {
-                    (GRID_WIDTH - 3) as i16
-                },
-                y: 2,
-            },
-            player_id,
-        ),
-    ]
+        spawn_segment_system(commands, tail, player_id, Segment::behind(&first, 1)),
+    ])
 }
 
 #[cfg(test)]
 mod test {
 
-    use crate::food::{self, Food};
+    use crate::{
+        controls::PlayerControls,
+        food::{Food, FoodKind},
+    };
 
     use super::*;
+    use crate::occupancy::rebuild_system;
+    use crate::speed::{tick_system, Ticks};
+    use bevy::ecs::system::RunSystemOnce;
 
     #[test]
     fn entity_has_snake_head() {
@@ -268,8 +569,11 @@ mod test {
         let mut app = App::new();
 
         // 2 Adicionar o `spawn_snake` startup system
-        app.insert_resource(Segments::default())
-            .add_systems(Startup, spawn_system);
+        app.insert_resource(GridConfig::new(10, 10))
+            .insert_resource(Segments::default())
+            .init_resource::<Rules>()
+            .init_resource::<Level>()
+            .add_systems(Startup, (spawn_system, rebuild_system).chain());
 
         // 3 Executar todos os sistemas pelo menos uma vez
         app.update();
@@ -281,13 +585,100 @@ mod test {
         assert_eq!(query.iter(&app.world).count(), 2); // Adicionar 2 snakes agora
     }
 
+    #[test]
+    fn spawns_one_snake_per_player() {
+        // Setup
+        let mut app = App::new();
+        app.insert_resource(GridConfig::new(20, 10))
+            .insert_resource(Segments::default())
+            .insert_resource(Rules {
+                players: 4,
+                ..default()
+            })
+            .init_resource::<Level>()
+            .add_systems(Startup, (spawn_system, rebuild_system).chain());
+        app.update();
+
+        let mut query = app.world.query::<(&Player, &Position, &Sprite)>();
+        let mut heads: Vec<(u8, i16)> = query
+            .iter(&app.world)
+            .filter(|(_, _, sprite)| sprite.color == SNAKE_HEAD_COLOR)
+            .map(|(player, position, _)| (player.id, position.x))
+            .collect();
+        heads.sort_unstable();
+        assert_eq!(heads, vec![(0, 4), (1, 8), (2, 12), (3, 16)]);
+
+        let segments = app.world.resource::<Segments>();
+        assert_eq!(segments.len(), 4);
+        assert!(segments.iter().all(|snake| snake.len() == 2));
+    }
+
+    #[test]
+    fn segments_know_their_owner_and_place() {
+        // Setup
+        let mut app = App::new();
+        app.insert_resource(GridConfig::new(10, 10))
+            .insert_resource(Segments::default())
+            .init_resource::<Rules>()
+            .init_resource::<Level>()
+            .insert_resource(LastTailPosition::default())
+            .add_event::<GameEndEvent>()
+            .add_event::<GrowthEvent>()
+            .add_systems(Startup, (spawn_system, rebuild_system).chain())
+            .add_systems(Update, growth_system);
+        app.world.send_event(GrowthEvent {
+            player_id: 1,
+            amount: 2,
+        });
+        app.update();
+        // Places, read against the head's stamp, hold up while the tail keeps
+        // moving up behind the head
+        for _ in 0..3 {
+            app.world.run_system_once(movement_system);
+        }
+
+        let segments = app.world.resource::<Segments>();
+        let ordered: Vec<Entity> = segments[1].iter().copied().collect();
+        let head = *app.world.get::<Segment>(ordered[0]).unwrap();
+        for (index, entity) in ordered.into_iter().enumerate() {
+            assert_eq!(
+                app.world.get::<Segment>(entity).unwrap().place(&head),
+                Some(index)
+            );
+        }
+        let mut query = app.world.query::<(&Segment, &Player)>();
+        let mut second: Vec<Option<usize>> = query
+            .iter(&app.world)
+            .filter(|(_, player)| player.id == 1)
+            .map(|(segment, _)| segment.place(&head))
+            .collect();
+        second.sort_unstable();
+        assert_eq!(second, vec![Some(0), Some(1), Some(2), Some(3)]);
+        let ahead = Segment {
+            stamp: head.stamp + 1,
+        };
+        assert_eq!(ahead.place(&head), None);
+    }
+
+    #[test]
+    fn every_player_has_its_own_colour() {
+        let colours: Vec<Color> = (0..8).map(segment_color).collect();
+
+        for (index, colour) in colours.iter().enumerate() {
+            assert!(!colours[index + 1..].contains(colour));
+        }
+    }
+
     #[test]
     fn snake_starts_moviment_up() {
         // Setup app
         let mut app = App::new();
         // Add startup system
-        app.insert_resource(Segments::default())
-            .add_systems(Startup, spawn_system);
+        app.insert_resource(GridConfig::new(10, 10))
+            .insert_resource(Segments::default())
+            .init_resource::<Rules>()
+            .init_resource::<Level>()
+            .add_systems(Startup, (spawn_system, rebuild_system).chain());
         // Run systems
         app.update();
         let mut query = app.world.query::<&Head>();
@@ -301,17 +692,18 @@ mod test {
         let mut app = App::new();
         // Adicionar positions para os dois players
         let p1_default_position = Position { x: 3, y: 4 };
-        #[cfg(debug_assertions)]
         let p2_default_position = Position { x: 7, y: 4 };
-        #[cfg(not(debug_assertions))]
-        let p2_default_position = Position { x: 17, y: 4 };
 
         // Adicionando sistemas
-        app.insert_resource(Segments::default())
+        app.insert_resource(GridConfig::new(10, 10))
+            .insert_resource(Segments::default())
+            .init_resource::<Rules>()
+            .init_resource::<Level>()
             .insert_resource(LastTailPosition::default())
             .add_event::<GameEndEvent>()
-            .add_systems(Startup, spawn_system)
+            .add_systems(Startup, (spawn_system, rebuild_system).chain())
             .add_systems(Update, movement_system)
+            .init_resource::<Controls>()
             .add_systems(Update, movement_input_system.before(movement_system));
 
         // Adicionando inputs de `KeyCode`s
@@ -341,17 +733,18 @@ mod test {
         let mut app = App::new();
         // Mudar as positions para 2 players
         let p1_up_position = Position { x: 3, y: 4 };
-        #[cfg(debug_assertions)]
         let p2_up_position = Position { x: 7, y: 4 };
-        #[cfg(not(debug_assertions))]
-        let p2_up_position = Position { x: 17, y: 4 };
 
         // Adiciona systemas
-        app.insert_resource(Segments::default())
+        app.insert_resource(GridConfig::new(10, 10))
+            .insert_resource(Segments::default())
+            .init_resource::<Rules>()
+            .init_resource::<Level>()
             .insert_resource(LastTailPosition::default())
             .add_event::<GameEndEvent>()
-            .add_systems(Startup, spawn_system)
+            .add_systems(Startup, (spawn_system, rebuild_system).chain())
             .add_systems(Update, movement_system)
+            .init_resource::<Controls>()
             .add_systems(Update, movement_input_system.before(movement_system));
 
         // Testa movimento para cima
@@ -374,10 +767,7 @@ mod test {
 
         // Adicionar o movimento para 2 players
         let p1_up_right_position = Position { x: 4, y: 4 };
-        #[cfg(debug_assertions)]
         let p2_up_right_position = Position { x: 7, y: 5 };
-        #[cfg(not(debug_assertions))]
-        let p2_up_right_position = Position { x: 17, y: 5 };
 
         // Testa movimento para direita
         let mut input = ButtonInput::<KeyCode>::default();
@@ -406,16 +796,17 @@ mod test {
         let mut app = App::new();
         // Adicionar a positions para 2 players
         let down_left_position = Position { x: 2, y: 4 };
-        #[cfg(debug_assertions)]
         let p2_up_position = Position { x: 7, y: 5 };
-        #[cfg(not(debug_assertions))]
-        let p2_up_position = Position { x: 17, y: 5 };
 
-        app.insert_resource(Segments::default())
+        app.insert_resource(GridConfig::new(10, 10))
+            .insert_resource(Segments::default())
+            .init_resource::<Rules>()
+            .init_resource::<Level>()
             .insert_resource(LastTailPosition::default())
             .add_event::<GameEndEvent>()
-            .add_systems(Startup, spawn_system)
+            .add_systems(Startup, (spawn_system, rebuild_system).chain())
             .add_systems(Update, movement_system)
+            .init_resource::<Controls>()
             .add_systems(Update, movement_input_system.before(movement_system));
 
         // Movimenta para baixo
@@ -451,17 +842,18 @@ mod test {
         let mut app = App::new();
         // Adicionar positions para 2 players
         let p1_down_left_position = Position { x: 3, y: 4 };
-        #[cfg(debug_assertions)]
         let p2_down_left_position = Position { x: 7, y: 4 };
-        #[cfg(not(debug_assertions))]
-        let p2_down_left_position = Position { x: 17, y: 4 };
 
         // Add systems
-        app.insert_resource(Segments::default())
+        app.insert_resource(GridConfig::new(10, 10))
+            .insert_resource(Segments::default())
+            .init_resource::<Rules>()
+            .init_resource::<Level>()
             .insert_resource(LastTailPosition::default())
             .add_event::<GameEndEvent>() // <--
-            .add_systems(Startup, spawn_system)
+            .add_systems(Startup, (spawn_system, rebuild_system).chain())
             .add_systems(Update, movement_system)
+            .init_resource::<Controls>()
             .add_systems(Update, movement_input_system.before(movement_system));
 
         // Move down
@@ -483,14 +875,546 @@ mod test {
             })
     }
 
+    #[test]
+    fn snake_follows_rebound_controls() {
+        // Setup
+        let mut app = App::new();
+        let mut controls = Controls::default();
+        controls.set(
+            0,
+            PlayerControls::keys(KeyCode::KeyZ, KeyCode::KeyQ, KeyCode::KeyS, KeyCode::KeyD),
+        );
+
+        app.insert_resource(GridConfig::new(10, 10))
+            .insert_resource(Segments::default())
+            .init_resource::<Rules>()
+            .init_resource::<Level>()
+            .insert_resource(LastTailPosition::default())
+            .insert_resource(controls)
+            .add_event::<GameEndEvent>()
+            .add_systems(Startup, (spawn_system, rebuild_system).chain())
+            .add_systems(Update, movement_system)
+            .add_systems(Update, movement_input_system.before(movement_system));
+
+        // A (QWERTY left) is no longer bound, Q is
+        let mut input = ButtonInput::<KeyCode>::default();
+        input.press(KeyCode::KeyA);
+        app.insert_resource(input);
+        app.update();
+
+        let mut input = ButtonInput::<KeyCode>::default();
+        input.press(KeyCode::KeyQ);
+        app.insert_resource(input);
+        app.update();
+
+        let mut query = app.world.query::<(&Head, &Position, &Player)>();
+        query
+            .iter(&app.world)
+            .filter(|(_, _, player)| player.id == 0)
+            .for_each(|(head, position, _)| {
+                assert_eq!(&Position { x: 2, y: 4 }, position);
+                assert_eq!(head.direction, Direction::Left);
+            });
+    }
+
+    #[test]
+    fn snake_moves_one_cell_per_fixed_step() {
+        // Setup
+        let mut app = App::new();
+        app.insert_resource(GridConfig::new(10, 10))
+            .insert_resource(Segments::default())
+            .init_resource::<Rules>()
+            .init_resource::<Level>()
+            .init_resource::<TickRate>()
+            .init_resource::<Ticks>()
+            .init_resource::<Time<Fixed>>()
+            .insert_resource(LastTailPosition::default())
+            .add_event::<GameEndEvent>()
+            .add_systems(Startup, (spawn_system, rebuild_system).chain())
+            .add_systems(FixedUpdate, (tick_system, movement_system).chain());
+        app.world.run_schedule(Startup);
+
+        for _ in 0..3 {
+            app.world.run_schedule(FixedUpdate);
+        }
+
+        assert_eq!(**app.world.resource::<Ticks>(), 3);
+        let mut query = app
+            .world
+            .query_filtered::<(&Position, &Player), With<Head>>();
+        let (head, _) = query
+            .iter(&app.world)
+            .find(|(_, player)| player.id == 0)
+            .unwrap();
+        assert_eq!(head, &Position { x: 3, y: 6 });
+    }
+
+    /// Runs one move of snakes laid out on their cells, head first, each
+    /// turning the given way, and returns the crashes by player.
+    fn move_snakes(rules: Rules, snakes: &[(&[(i16, i16)], Direction)]) -> Vec<GameEndEvent> {
+        move_snakes_with_food(rules, snakes, &[])
+    }
+
+    fn move_snakes_with_food(
+        rules: Rules,
+        snakes: &[(&[(i16, i16)], Direction)],
+        food: &[(i16, i16)],
+    ) -> Vec<GameEndEvent> {
+        let mut app = board_with_snakes(rules, snakes, food);
+        app.world.run_system_once(movement_system);
+        let mut crashes: Vec<GameEndEvent> = app
+            .world
+            .resource_mut::<Events<GameEndEvent>>()
+            .drain()
+            .collect();
+        crashes.sort_by_key(|crash| match crash {
+            GameEndEvent::Crashed { player_id, .. } => *player_id,
+            GameEndEvent::BoardCleared => u8::MAX,
+        });
+        crashes
+    }
+
+    /// Snakes laid out head first, each about to turn the given way.
+    fn board_with_snakes(
+        rules: Rules,
+        snakes: &[(&[(i16, i16)], Direction)],
+        food: &[(i16, i16)],
+    ) -> App {
+        let mut app = App::new();
+        app.insert_resource(GridConfig::new(10, 10))
+            .insert_resource(rules)
+            .insert_resource(LastTailPosition::default())
+            .add_event::<GameEndEvent>();
+        let snakes: Vec<VecDeque<Entity>> = (0..)
+            .zip(snakes)
+            .map(|(id, (cells, turn))| {
+                cells
+                    .iter()
+                    .enumerate()
+                    .map(|(index, &(x, y))| {
+                        let mut segment = app.world.spawn((
+                            Segment::behind(&Segment { stamp: 0 }, index),
+                            Player { id },
+                            Position { x, y },
+                        ));
+                        if index == 0 {
+                            let mut head = Head::default();
+                            head.queue_turn(*turn);
+                            segment.insert(head);
+                        }
+                        segment.id()
+                    })
+                    .collect()
+            })
+            .collect();
+        app.insert_resource(Segments(snakes));
+        for &(x, y) in food {
+            app.world.spawn((Food::default(), Position { x, y }));
+        }
+        app.world.run_system_once(rebuild_system);
+        app
+    }
+
+    fn move_coiled_snake(cells: &[(i16, i16)]) -> Vec<GameEndEvent> {
+        move_snakes(Rules::default(), &[(cells, Direction::Right)])
+    }
+
+    fn head_on(players: &[u8]) -> Vec<GameEndEvent> {
+        players
+            .iter()
+            .map(|player_id| GameEndEvent::Crashed {
+                player_id: *player_id,
+                cause: DeathCause::HeadOn,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn tail_of_a_snake_that_eats_stays_in_the_way() {
+        // The second snake's head moves into the first snake's tail cell
+        let crashes = move_snakes_with_food(
+            Rules::default(),
+            &[
+                (&[(3, 5), (3, 4), (3, 3)], Direction::Up),
+                (&[(4, 3), (5, 3)], Direction::Left),
+            ],
+            &[(3, 6)],
+        );
+
+        assert_eq!(
+            crashes,
+            vec![GameEndEvent::Crashed {
+                player_id: 1,
+                cause: DeathCause::OpponentBody
+            }]
+        );
+    }
+
+    #[test]
+    fn heads_meeting_on_food_leave_it_to_the_winner() {
+        let mut app = board_with_snakes(
+            Rules {
+                head_on: HeadOnRule::LongerWins,
+                ..default()
+            },
+            &[
+                (&[(3, 5), (2, 5), (1, 5)], Direction::Right),
+                (&[(5, 5), (6, 5)], Direction::Left),
+            ],
+            &[(4, 5)],
+        );
+        app.init_resource::<TickRate>().add_event::<GrowthEvent>();
+
+        app.world.run_system_once(movement_system);
+        app.world.run_system_once(eating_system);
+        app.world.run_system_once(growth_system);
+
+        let growth: Vec<(u8, i16)> = app
+            .world
+            .resource_mut::<Events<GrowthEvent>>()
+            .drain()
+            .map(|event| (event.player_id, event.amount))
+            .collect();
+        assert_eq!(growth, vec![(0, 1)]);
+        let crashes: Vec<GameEndEvent> = app
+            .world
+            .resource_mut::<Events<GameEndEvent>>()
+            .drain()
+            .collect();
+        assert_eq!(crashes, head_on(&[1]));
+        let mut query = app.world.query::<&Food>();
+        assert_eq!(query.iter(&app.world).count(), 0);
+        let segments = app.world.resource::<Segments>();
+        assert_eq!((segments[0].len(), segments[1].len()), (4, 2));
+        let mut query = app.world.query::<(&Head, &Player)>();
+        for (head, player) in query.iter(&app.world) {
+            assert_eq!(head.eats, player.id == 0);
+            assert!(!head.grew);
+        }
+        let mut query = app.world.query::<&Segment>();
+        assert_eq!(query.iter(&app.world).count(), 4 + 2);
+        assert!(app
+            .world
+            .resource::<Occupancy>()
+            .get(&Position { x: 4, y: 5 })
+            .iter()
+            .all(|occupant| *occupant != Occupant::Food));
+    }
+
+    #[test]
+    fn tail_of_a_snake_that_does_not_eat_makes_way() {
+        let crashes = move_snakes(
+            Rules::default(),
+            &[
+                (&[(3, 5), (3, 4), (3, 3)], Direction::Up),
+                (&[(4, 3), (5, 3)], Direction::Left),
+            ],
+        );
+
+        assert!(crashes.is_empty());
+    }
+
+    #[test]
+    fn heads_meeting_in_one_cell_both_die() {
+        let crashes = move_snakes(
+            Rules::default(),
+            &[
+                (&[(3, 5), (2, 5)], Direction::Right),
+                (&[(5, 5), (6, 5), (7, 5)], Direction::Left),
+            ],
+        );
+
+        assert_eq!(crashes, head_on(&[0, 1]));
+    }
+
+    #[test]
+    fn heads_swapping_cells_do_not_pass_through() {
+        let crashes = move_snakes(
+            Rules::default(),
+            &[
+                (&[(3, 5), (2, 5)], Direction::Right),
+                (&[(4, 5), (5, 5)], Direction::Left),
+            ],
+        );
+
+        assert_eq!(crashes, head_on(&[0, 1]));
+    }
+
+    #[test]
+    fn longer_snake_wins_head_on_when_configured() {
+        let rules = Rules {
+            head_on: HeadOnRule::LongerWins,
+            ..default()
+        };
+        let same_cell = move_snakes(
+            rules.clone(),
+            &[
+                (&[(3, 5), (2, 5)], Direction::Right),
+                (&[(5, 5), (6, 5), (7, 5)], Direction::Left),
+            ],
+        );
+        let swap = move_snakes(
+            rules.clone(),
+            &[
+                (&[(3, 5), (2, 5), (1, 5)], Direction::Right),
+                (&[(4, 5), (5, 5)], Direction::Left),
+            ],
+        );
+        let even = move_snakes(
+            rules,
+            &[
+                (&[(3, 5), (2, 5)], Direction::Right),
+                (&[(4, 5), (5, 5)], Direction::Left),
+            ],
+        );
+
+        assert_eq!(same_cell, head_on(&[0]));
+        assert_eq!(swap, head_on(&[1]));
+        assert_eq!(even, head_on(&[0, 1]));
+    }
+
+    #[test]
+    fn head_may_follow_its_own_tail() {
+        let crashes = move_coiled_snake(&[(3, 3), (3, 2), (4, 2), (4, 3)]);
+
+        assert!(crashes.is_empty());
+    }
+
+    #[test]
+    fn only_the_tail_moves_up_behind_the_head() {
+        // Setup
+        let mut app = App::new();
+        app.insert_resource(GridConfig::new(10, 10))
+            .insert_resource(Segments::default())
+            .insert_resource(Rules {
+                players: 1,
+                ..default()
+            })
+            .init_resource::<Level>()
+            .insert_resource(LastTailPosition::default())
+            .add_event::<GameEndEvent>()
+            .add_event::<GrowthEvent>()
+            .add_systems(Startup, (spawn_system, rebuild_system).chain())
+            .add_systems(Update, growth_system);
+        app.world.send_event(GrowthEvent {
+            player_id: 0,
+            amount: 2,
+        });
+        app.update();
+        let before: Vec<Entity> = app.world.resource::<Segments>()[0]
+            .iter()
+            .copied()
+            .collect();
+        let position = |app: &App, entity: Entity| app.world.get::<Position>(entity).cloned();
+        let body: Vec<Option<Position>> = before[1..3]
+            .iter()
+            .map(|entity| position(&app, *entity))
+            .collect();
+
+        app.world.run_system_once(movement_system);
+
+        let after: Vec<Entity> = app.world.resource::<Segments>()[0]
+            .iter()
+            .copied()
+            .collect();
+        assert_eq!(after, vec![before[0], before[3], before[1], before[2]]);
+        assert_eq!(position(&app, before[0]), Some(Position { x: 5, y: 4 }));
+        assert_eq!(position(&app, before[3]), Some(Position { x: 5, y: 3 }));
+        let unmoved: Vec<Option<Position>> = before[1..3]
+            .iter()
+            .map(|entity| position(&app, *entity))
+            .collect();
+        assert_eq!(unmoved, body);
+        assert_eq!(
+            app.world.resource::<LastTailPosition>().get(0),
+            Some(&Position { x: 5, y: 2 })
+        );
+    }
+
+    #[test]
+    fn tail_that_just_grew_stays_in_the_way() {
+        // Two segments stacked on the tail cell, as right after growing
+        let crashes = move_coiled_snake(&[(3, 3), (3, 2), (4, 2), (4, 3), (4, 3)]);
+
+        assert_eq!(
+            crashes,
+            vec![GameEndEvent::Crashed {
+                player_id: 0,
+                cause: DeathCause::OwnBody
+            }]
+        );
+    }
+
+    #[test]
+    fn turns_are_queued_without_repeats_or_reversals() {
+        let mut head = Head::default();
+
+        head.queue_turn(Direction::Up);
+        head.queue_turn(Direction::Down);
+        head.queue_turn(Direction::Left);
+        head.queue_turn(Direction::Left);
+        head.queue_turn(Direction::Right);
+        head.queue_turn(Direction::Down);
+        head.queue_turn(Direction::Right);
+        head.queue_turn(Direction::Up);
+        head.queue_turn(Direction::Left);
+
+        let moves: Vec<Direction> = (0..4).map(|_| head.advance()).collect();
+        assert_eq!(
+            moves,
+            vec![
+                Direction::Left,
+                Direction::Down,
+                Direction::Right,
+                Direction::Right
+            ]
+        );
+    }
+
+    #[test]
+    fn quick_turns_between_moves_are_all_taken() {
+        // Setup
+        let mut app = App::new();
+        app.insert_resource(GridConfig::new(10, 10))
+            .insert_resource(Segments::default())
+            .init_resource::<Rules>()
+            .init_resource::<Level>()
+            .init_resource::<Controls>()
+            .insert_resource(LastTailPosition::default())
+            .insert_resource(ButtonInput::<KeyCode>::default())
+            .add_event::<GameEndEvent>()
+            .add_systems(Startup, (spawn_system, rebuild_system).chain())
+            .add_systems(Update, movement_input_system);
+        app.update();
+
+        // Left then down, both pressed before the snake moves again
+        for key in [KeyCode::KeyA, KeyCode::KeyS] {
+            let mut input = ButtonInput::<KeyCode>::default();
+            input.press(key);
+            app.insert_resource(input);
+            app.update();
+        }
+        app.world.run_system_once(movement_system);
+        app.world.run_system_once(movement_system);
+
+        let mut query = app.world.query::<(&Head, &Position, &Player)>();
+        let (head, position, _) = query
+            .iter(&app.world)
+            .find(|(_, _, player)| player.id == 0)
+            .unwrap();
+        assert_eq!(position, &Position { x: 2, y: 2 });
+        assert_eq!(head.direction(), Direction::Down);
+        assert!(app.world.resource::<Events<GameEndEvent>>().is_empty());
+    }
+
+    #[test]
+    fn snake_wraps_around_edges_when_walls_wrap() {
+        // Setup
+        let mut app = App::new();
+        app.insert_resource(GridConfig::new(10, 10))
+            .insert_resource(Segments::default())
+            .insert_resource(Rules {
+                wrap_walls: true,
+                ..default()
+            })
+            .init_resource::<Level>()
+            .insert_resource(LastTailPosition::default())
+            .init_resource::<Controls>()
+            .add_event::<GameEndEvent>()
+            .add_systems(Startup, (spawn_system, rebuild_system).chain())
+            .add_systems(Update, movement_system)
+            .add_systems(Update, movement_input_system.before(movement_system));
+
+        let mut input = ButtonInput::<KeyCode>::default();
+        input.press(KeyCode::KeyA);
+        app.insert_resource(input);
+
+        // From x = 3 the fourth step leaves the board on the left
+        for _ in 0..4 {
+            app.update();
+        }
+
+        let mut query = app.world.query::<(&Head, &Position, &Player)>();
+        query
+            .iter(&app.world)
+            .filter(|(_, _, player)| player.id == 0)
+            .for_each(|(_, position, _)| {
+                assert_eq!(&Position { x: 9, y: 3 }, position);
+            });
+        let mut query = app.world.query::<(&Segment, &Position)>();
+        assert!(query
+            .iter(&app.world)
+            .any(|(_, position)| position == &Position { x: 0, y: 3 }));
+        assert!(app.world.resource::<Events<GameEndEvent>>().is_empty());
+    }
+
+    #[test]
+    fn snakes_start_at_level_spawns_and_die_on_level_walls() {
+        // Setup
+        let mut app = App::new();
+        let level = Level::parse(".#...\n..#..\n....2\n.1...\n.....").unwrap();
+        app.insert_resource(level.grid.unwrap())
+            .insert_resource(Segments::default())
+            .init_resource::<Rules>()
+            .insert_resource(level)
+            .insert_resource(LastTailPosition::default())
+            .add_event::<GameEndEvent>()
+            .add_systems(
+                Startup,
+                (
+                    (spawn_system, crate::level::spawn_walls_system),
+                    rebuild_system,
+                )
+                    .chain(),
+            )
+            .add_systems(Update, movement_system);
+
+        app.update();
+
+        let mut query = app.world.query::<(&Head, &Position, &Player)>();
+        let mut heads: Vec<(u8, Position)> = query
+            .iter(&app.world)
+            .map(|(_, position, player)| (player.id, position.clone()))
+            .collect();
+        heads.sort_unstable_by_key(|(id, _)| *id);
+        // Both moved up once from their spawns
+        assert_eq!(
+            heads,
+            vec![(0, Position { x: 1, y: 2 }), (1, Position { x: 4, y: 3 })]
+        );
+
+        app.update();
+        app.update();
+
+        // Player 1 ran into the level wall, player 2 off the board
+        let events: Vec<GameEndEvent> = app
+            .world
+            .resource_mut::<Events<GameEndEvent>>()
+            .drain()
+            .collect();
+        assert_eq!(
+            events,
+            vec![0, 1]
+                .into_iter()
+                .map(|player_id| GameEndEvent::Crashed {
+                    player_id,
+                    cause: DeathCause::Wall
+                })
+                .collect::<Vec<_>>()
+        );
+    }
+
     #[test]
     fn entity_snake_has_two_segments() {
         // Setup app
         let mut app = App::new();
 
         // Adicionar sistema de spawn e recurso com segmentos
-        app.insert_resource(Segments::default())
-            .add_systems(Startup, spawn_system);
+        app.insert_resource(GridConfig::new(10, 10))
+            .insert_resource(Segments::default())
+            .init_resource::<Rules>()
+            .init_resource::<Level>()
+            .add_systems(Startup, (spawn_system, rebuild_system).chain());
 
         // Executar sistema
         app.update();
@@ -509,11 +1433,15 @@ mod test {
         let new_position_segment_right = Position { x: 3, y: 3 };
 
         // Adiciona os systemas
-        app.insert_resource(Segments::default())
+        app.insert_resource(GridConfig::new(10, 10))
+            .insert_resource(Segments::default())
+            .init_resource::<Rules>()
+            .init_resource::<Level>()
             .insert_resource(LastTailPosition::default())
             .add_event::<GameEndEvent>()
-            .add_systems(Startup, spawn_system)
+            .add_systems(Startup, (spawn_system, rebuild_system).chain())
             .add_systems(Update, movement_system)
+            .init_resource::<Controls>()
             .add_systems(Update, movement_input_system.before(movement_system));
 
         // adiciona resource apertando a tecla D, movimento para direita
@@ -538,14 +1466,13 @@ mod test {
 
         let mut query = app
             .world
-            .query_filtered::<(&Head, &Position, &Player), Without<Head>>(); // <-- Alterar adiconando o player
+            .query_filtered::<(&Segment, &Position, &Player), Without<Head>>(); // <-- Alterar adiconando o player
         query
             .iter(&app.world)
             .filter(|(_, _, player)| player.id == 0) // <-- Adicionar um filtro
-            .for_each(|(head, position, _)| {
+            .for_each(|(_segment, position, _)| {
                 // garante que nova posição do segmento é esperada:
                 assert_eq!(&new_position_segment_right, position);
-                assert_eq!(head.direction, Direction::Right);
             });
 
         // NOVAS POSIÇÕES ESPERADAS
@@ -585,21 +1512,29 @@ mod test {
             })
     }
 
-    #[test]
-    fn snake_grows_when_eating() {
-        // Setup
+    fn app_with_food(kind: FoodKind) -> App {
         let mut app = App::new();
-
-        // sistemas
-        app.insert_resource(Segments::default())
+        app.insert_resource(GridConfig::new(10, 10))
+            .insert_resource(Segments::default())
+            .init_resource::<Rules>()
+            .init_resource::<Level>()
+            .init_resource::<TickRate>()
             .insert_resource(LastTailPosition::default())
             .add_event::<GameEndEvent>()
             .add_event::<GrowthEvent>()
-            .add_systems(Startup, spawn_system)
-            .add_systems(Update, food::spawn_system)
+            .add_systems(Startup, (spawn_system, rebuild_system).chain())
             .add_systems(Update, movement_system)
             .add_systems(Update, eating_system.after(movement_system))
             .add_systems(Update, growth_system.after(eating_system));
+        // Right in front of the first snake
+        app.world.spawn((Food { kind }, Position { x: 3, y: 5 }));
+        app
+    }
+
+    #[test]
+    fn snake_grows_when_eating() {
+        // Setup
+        let mut app = app_with_food(FoodKind::Normal);
 
         // update de configuração
         app.update();
@@ -615,4 +1550,130 @@ mod test {
         let mut query = app.world.query::<(&Segment, &Position)>();
         assert_eq!(query.iter(&app.world).count(), 5); // <-- Alterar pra 2 players
     }
+
+    #[test]
+    fn snakes_eating_in_the_same_tick_grow_at_their_own_tails() {
+        // Setup
+        let mut app = app_with_food(FoodKind::Normal);
+        // Right in front of the second snake too
+        app.world.spawn((Food::default(), Position { x: 7, y: 5 }));
+
+        app.update();
+        app.update();
+
+        let segments = app.world.resource::<Segments>();
+        let tails: Vec<Entity> = segments.iter().map(|snake| snake[2]).collect();
+        let mut query = app.world.query::<&Position>();
+        assert_eq!(
+            query.get_many(&app.world, [tails[0], tails[1]]).unwrap(),
+            [&Position { x: 3, y: 3 }, &Position { x: 7, y: 3 }]
+        );
+    }
+
+    #[test]
+    fn growth_before_the_first_move_does_not_panic() {
+        // Setup
+        let mut app = App::new();
+        app.insert_resource(GridConfig::new(10, 10))
+            .insert_resource(Segments::default())
+            .init_resource::<Rules>()
+            .init_resource::<Level>()
+            .insert_resource(LastTailPosition::default())
+            .add_event::<GrowthEvent>()
+            .add_systems(Startup, (spawn_system, rebuild_system).chain())
+            .add_systems(Update, growth_system);
+        app.world.send_event(GrowthEvent {
+            player_id: 1,
+            amount: 1,
+        });
+
+        app.update();
+
+        let segments = app.world.resource::<Segments>();
+        assert_eq!(segments[0].len(), 2);
+        assert_eq!(segments[1].len(), 3);
+    }
+
+    #[test]
+    fn golden_food_grows_several_segments() {
+        // Setup
+        let mut app = app_with_food(FoodKind::Golden);
+
+        app.update();
+        app.update();
+
+        let segments = app.world.resource::<Segments>();
+        assert_eq!(segments[0].len(), 5);
+        assert_eq!(segments[1].len(), 2);
+    }
+
+    #[test]
+    fn poison_shrinks_but_never_below_starting_length() {
+        // Setup
+        let mut app = app_with_food(FoodKind::Poison);
+        app.world.send_event(GrowthEvent {
+            player_id: 0,
+            amount: 3,
+        });
+        app.update();
+        assert_eq!(app.world.resource::<Segments>()[0].len(), 5);
+
+        // Eating the poison takes two segments away
+        app.update();
+        assert_eq!(app.world.resource::<Segments>()[0].len(), 3);
+
+        app.world.send_event(GrowthEvent {
+            player_id: 0,
+            amount: -5,
+        });
+        app.update();
+        assert_eq!(app.world.resource::<Segments>()[0].len(), 2);
+        let mut query = app.world.query::<&Segment>();
+        assert_eq!(query.iter(&app.world).count(), 2 + 2);
+    }
+
+    #[test]
+    fn occupancy_follows_moves_growth_and_eating() {
+        // Setup
+        let mut app = app_with_food(FoodKind::Poison);
+        app.world.send_event(GrowthEvent {
+            player_id: 1,
+            amount: 3,
+        });
+        for _ in 0..4 {
+            app.update();
+        }
+
+        let kept = app.world.resource::<Occupancy>().clone();
+        app.world.run_system_once(rebuild_system);
+        let rebuilt = app.world.resource::<Occupancy>();
+        let sorted = |occupancy: &Occupancy, position: &Position| {
+            let mut occupants: Vec<String> = occupancy
+                .get(position)
+                .iter()
+                .map(|occupant| format!("{occupant:?}"))
+                .collect();
+            occupants.sort_unstable();
+            occupants
+        };
+        for position in GridConfig::new(10, 10).positions() {
+            assert_eq!(
+                sorted(&kept, &position),
+                sorted(rebuilt, &position),
+                "{position:?}"
+            );
+        }
+        assert_eq!(kept.free_cells(), 100 - 2 - 5);
+    }
+
+    #[test]
+    fn speed_food_changes_pace() {
+        // Setup
+        let mut app = app_with_food(FoodKind::Speed);
+
+        app.update();
+        app.update();
+
+        assert!(app.world.resource::<TickRate>().interval(2) < TickRate::default().interval(2));
+    }
 }
@@ -0,0 +1,227 @@
+use std::{collections::VecDeque, time::Duration};
+
+use bevy::prelude::*;
+
+use crate::snake::Segments;
+
+/// Length every snake starts with; the ramp only counts growth beyond it.
+const STARTING_LENGTH: usize = 2;
+/// Interval shaved off for every segment the longest snake has grown.
+const RAMP_STEP: Duration = Duration::from_millis(4);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    Insane,
+}
+
+impl Difficulty {
+    /// Time between two snake moves at the start of a round.
+    #[must_use]
+    pub const fn interval(self) -> Duration {
+        Duration::from_millis(match self {
+            Difficulty::Easy => 200,
+            Difficulty::Normal => 150,
+            Difficulty::Hard => 100,
+            Difficulty::Insane => 60,
+        })
+    }
+}
+
+/// Paces snake movement by setting the fixed timestep the simulation runs
+/// at. With the ramp on, the game speeds up as the longest snake grows, down
+/// to a third of the starting interval. Speed and slow food stretch or shrink
+/// the interval for a while on top of that.
+#[derive(Resource, Clone, Debug, PartialEq, Eq)]
+pub struct TickRate {
+    base: Duration,
+    ramp: bool,
+    /// Percentage of the interval to use until the timer runs out.
+    modifier: Option<(u32, Timer)>,
+}
+
+impl Default for TickRate {
+    fn default() -> Self {
+        Self::new(Difficulty::default(), false)
+    }
+}
+
+impl TickRate {
+    #[must_use]
+    pub fn new(difficulty: Difficulty, ramp: bool) -> Self {
+        Self::fixed(difficulty.interval()).with_ramp(ramp)
+    }
+
+    /// Constant interval, mostly useful to pin the speed in tests.
+    #[must_use]
+    pub const fn fixed(interval: Duration) -> Self {
+        Self {
+            base: interval,
+            ramp: false,
+            modifier: None,
+        }
+    }
+
+    #[must_use]
+    pub const fn with_ramp(mut self, ramp: bool) -> Self {
+        self.ramp = ramp;
+        self
+    }
+
+    /// Interval between moves once the longest snake is `longest` segments long.
+    #[must_use]
+    pub fn interval(&self, longest: usize) -> Duration {
+        let interval = if self.ramp {
+            let grown = u32::try_from(longest.saturating_sub(STARTING_LENGTH)).unwrap_or(u32::MAX);
+            self.base
+                .saturating_sub(RAMP_STEP.saturating_mul(grown))
+                .max(self.base / 3)
+        } else {
+            self.base
+        };
+        match &self.modifier {
+            Some((percent, _)) => interval * *percent / 100,
+            None => interval,
+        }
+    }
+
+    /// Scales the interval to `percent` of its value for `duration`, replacing
+    /// any modifier still running.
+    pub fn modify(&mut self, percent: u32, duration: Duration) {
+        self.modifier = Some((percent, Timer::new(duration, TimerMode::Once)));
+    }
+
+    /// Starts the next round from the base interval again.
+    pub fn reset(&mut self) {
+        self.modifier = None;
+    }
+}
+
+/// Simulation steps run since the round started.
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq, Deref)]
+pub struct Ticks(u64);
+
+/// First step of every simulation tick: counts it and runs down the speed
+/// modifier in simulated time.
+#[allow(clippy::needless_pass_by_value)]
+pub fn tick_system(
+    fixed_time: Res<Time<Fixed>>,
+    mut ticks: ResMut<Ticks>,
+    mut tick_rate: ResMut<TickRate>,
+) {
+    ticks.0 += 1;
+    let expired = tick_rate
+        .modifier
+        .as_mut()
+        .is_some_and(|(_, timer)| timer.tick(fixed_time.timestep()).finished());
+    if expired {
+        tick_rate.modifier = None;
+    }
+}
+
+/// Keeps the fixed timestep in line with the current tick rate.
+#[allow(clippy::needless_pass_by_value)]
+pub fn pace_system(
+    segments: Res<Segments>,
+    tick_rate: Res<TickRate>,
+    mut fixed_time: ResMut<Time<Fixed>>,
+) {
+    let longest = segments.iter().map(VecDeque::len).max().unwrap_or(0);
+    let interval = tick_rate.interval(longest);
+    if fixed_time.timestep() != interval {
+        fixed_time.set_timestep(interval);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bevy::time::TimeUpdateStrategy;
+
+    #[test]
+    fn harder_difficulties_move_faster() {
+        assert!(Difficulty::Easy.interval() > Difficulty::Normal.interval());
+        assert!(Difficulty::Normal.interval() > Difficulty::Hard.interval());
+        assert!(Difficulty::Hard.interval() > Difficulty::Insane.interval());
+        assert_eq!(Difficulty::Normal.interval(), Duration::from_millis(150));
+    }
+
+    #[test]
+    fn ramp_shrinks_interval_as_snakes_grow() {
+        let tick_rate = TickRate::new(Difficulty::Normal, true);
+
+        assert_eq!(tick_rate.interval(2), Duration::from_millis(150));
+        assert_eq!(tick_rate.interval(7), Duration::from_millis(130));
+        assert_eq!(tick_rate.interval(500), Duration::from_millis(50));
+    }
+
+    #[test]
+    fn without_ramp_interval_stays_fixed() {
+        let tick_rate = TickRate::new(Difficulty::Hard, false);
+
+        assert_eq!(tick_rate.interval(40), Duration::from_millis(100));
+    }
+
+    fn paced_app(tick_rate: TickRate) -> App {
+        let mut app = App::new();
+        app.add_plugins(bevy::time::TimePlugin)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+                50,
+            )))
+            .insert_resource(Segments::default())
+            .insert_resource(tick_rate)
+            .init_resource::<Ticks>()
+            .add_systems(FixedUpdate, tick_system)
+            .add_systems(Update, pace_system);
+        app
+    }
+
+    #[test]
+    fn modifier_scales_interval_until_it_runs_out() {
+        // Setup
+        let mut app = paced_app(TickRate::fixed(Duration::from_millis(100)));
+        app.world
+            .resource_mut::<TickRate>()
+            .modify(50, Duration::from_millis(200));
+
+        assert_eq!(
+            app.world.resource::<TickRate>().interval(2),
+            Duration::from_millis(50)
+        );
+
+        // Four steps of 50ms
+        app.update();
+        for _ in 0..4 {
+            app.world.run_schedule(FixedUpdate);
+        }
+
+        assert_eq!(
+            app.world.resource::<TickRate>().interval(2),
+            Duration::from_millis(100)
+        );
+        assert_eq!(*app.world.resource::<Ticks>(), Ticks(4));
+    }
+
+    #[test]
+    fn fixed_timestep_follows_tick_rate() {
+        // Setup
+        let mut app = paced_app(TickRate::fixed(Duration::from_millis(100)));
+
+        let ticks: Vec<u64> = (0..5)
+            .map(|_| {
+                app.update();
+                **app.world.resource::<Ticks>()
+            })
+            .collect();
+
+        // The first update only starts the clock
+        assert_eq!(
+            app.world.resource::<Time<Fixed>>().timestep(),
+            Duration::from_millis(100)
+        );
+        assert_eq!(ticks, vec![0, 0, 1, 1, 2]);
+    }
+}